@@ -0,0 +1,570 @@
+//! Derive macros for the `rawcmd` command-line application framework.
+//!
+//! `#[derive(Command)]` on a struct turns its fields into flags, params and
+//! an optional subcommand while on an enum it turns variants into
+//! subcommands. Both generate `command()`, `command_with_resolver()`, a
+//! typed `from_intent()` conversion and an implementation of `FromIntent`.
+
+extern crate proc_macro;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument,
+    Ident, LitStr, PathArguments, Type};
+
+/// Derives the command definition and the `Intent` conversion.
+#[proc_macro_derive(Command, attributes(command, flag, param))]
+pub fn derive_command(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let result = match &input.data {
+        Data::Struct(data) => expand_struct(&input, &data.fields),
+        Data::Enum(data) => expand_enum(&input, data.variants.iter().collect()),
+        Data::Union(_) => Err(syn::Error::new_spanned(&input.ident, "unions can not derive `Command`")),
+    };
+    match result {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Options of the `#[command(...)]` attribute.
+#[derive(Default)]
+struct CommandAttrs {
+    name: Option<String>,
+    about: Option<String>,
+    description: Option<String>,
+    version: Option<String>,
+    author: Option<String>,
+    subcommand: bool,
+}
+
+/// Options of the `#[flag(...)]` and `#[param(...)]` attributes.
+#[derive(Default)]
+struct FieldAttrs {
+    name: Option<String>,
+    alias: Option<String>,
+    description: Option<String>,
+    default: Option<String>,
+    env: Option<String>,
+    count: bool,
+    negatable: bool,
+}
+
+/// Role of a struct field.
+enum FieldRole {
+    Flag(FieldAttrs),
+    Param(FieldAttrs),
+    Subcommand,
+}
+
+/// Shape of a field type.
+enum Shape<'a> {
+    Bool,
+    Option(&'a Type),
+    Vec(&'a Type),
+    Plain(&'a Type),
+}
+
+fn expand_struct(input: &DeriveInput, fields: &Fields) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let attrs = parse_command_attrs(&input.attrs)?;
+    let name = attrs.name.clone().unwrap_or_else(|| kebab_case(&ident.to_string()));
+    let fields = match fields {
+        Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+        Fields::Unit => Vec::new(),
+        Fields::Unnamed(_) => {
+            return Err(syn::Error::new_spanned(ident, "tuple structs can not derive `Command`"));
+        },
+    };
+
+    let mut definitions = Vec::new();
+    let mut conversions = Vec::new();
+    let mut resolvable = true;
+    let mut param_index = 0usize;
+    for field in fields.iter() {
+        let field_ident = field.ident.as_ref().unwrap();
+        let value = match parse_field_role(field_ident, &field.attrs)? {
+            FieldRole::Flag(attrs) => {
+                let flag_name = attrs.name.clone().unwrap_or_else(|| kebab_case(&field_ident.to_string()));
+                definitions.push(build_flag(&flag_name, &attrs, &field.ty));
+                convert_flag(&flag_name, &attrs, &field.ty)
+            },
+            FieldRole::Param(attrs) => {
+                let param_name = attrs.name.clone().unwrap_or_else(|| field_ident.to_string());
+                definitions.push(build_param(&param_name, &attrs, &field.ty));
+                param_index += 1;
+                convert_param(&param_name, param_index - 1, &attrs, &field.ty)
+            },
+            FieldRole::Subcommand => {
+                let (ty, optional) = match shape(&field.ty) {
+                    Shape::Option(ty) => (ty, true),
+                    _ => (&field.ty, false),
+                };
+                definitions.push(quote! {
+                    let command = <#ty>::__rawcmd_subcommands(resolver).into_iter()
+                        .fold(command, |command, subcommand| command.with_subcommand(subcommand));
+                });
+                match optional {
+                    true => quote! { <#ty>::__rawcmd_from_path(intent, depth + 1)? },
+                    false => {
+                        resolvable = false;
+                        quote! {
+                            <#ty>::__rawcmd_from_path(intent, depth + 1)?.ok_or_else(|| {
+                                ::rawcmd::Error::new(::rawcmd::ErrorKind::MissingCommandResolver(#name.to_string()))
+                            })?
+                        }
+                    },
+                }
+            },
+        };
+        conversions.push(quote! { #field_ident: #value });
+    }
+
+    let header = build_command_header(&attrs);
+    let api = build_public_api(ident);
+    let finish = match resolvable {
+        true => quote! {
+            match resolver {
+                Some(resolver) => {
+                    let resolver = resolver.clone();
+                    command.with_resolver(move |intent, ctx| resolver(intent, ctx))
+                },
+                None => command,
+            }
+        },
+        false => quote! { command },
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #api
+
+            #[doc(hidden)]
+            pub fn __rawcmd_command<C: 'static>(
+                name: &str,
+                resolver: &Option<::std::rc::Rc<dyn Fn(&::rawcmd::Intent, &mut C) -> ::rawcmd::Result<i32>>>,
+            ) -> ::rawcmd::Command<C> {
+                let _ = resolver;
+                let command = ::rawcmd::Command::with_name(name) #header;
+                #(#definitions)*
+                #finish
+            }
+
+            #[doc(hidden)]
+            pub fn __rawcmd_from_intent(intent: &::rawcmd::Intent, depth: usize) -> ::rawcmd::Result<Self> {
+                let _ = depth;
+                Ok(Self {
+                    #(#conversions,)*
+                })
+            }
+
+            #[doc(hidden)]
+            pub fn __rawcmd_name() -> &'static str {
+                #name
+            }
+        }
+
+        impl #impl_generics ::rawcmd::FromIntent for #ident #ty_generics #where_clause {
+            fn from_intent(intent: &::rawcmd::Intent) -> ::rawcmd::Result<Self> {
+                Self::__rawcmd_from_intent(intent, 0)
+            }
+        }
+    })
+}
+
+fn expand_enum(input: &DeriveInput, variants: Vec<&syn::Variant>) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let attrs = parse_command_attrs(&input.attrs)?;
+    let name = attrs.name.clone().unwrap_or_else(|| kebab_case(&ident.to_string()));
+
+    let mut subcommands = Vec::new();
+    let mut names = Vec::new();
+    let mut conversions = Vec::new();
+    for variant in variants.iter() {
+        let variant_ident = &variant.ident;
+        let variant_attrs = parse_command_attrs(&variant.attrs)?;
+        let variant_name = variant_attrs.name.clone().unwrap_or_else(|| kebab_case(&variant_ident.to_string()));
+        let header = build_command_header(&variant_attrs);
+        match &variant.fields {
+            Fields::Unit => {
+                subcommands.push(quote! {
+                    match resolver {
+                        Some(resolver) => {
+                            let resolver = resolver.clone();
+                            ::rawcmd::Command::with_name(#variant_name) #header
+                                .with_resolver(move |intent, ctx| resolver(intent, ctx))
+                        },
+                        None => ::rawcmd::Command::with_name(#variant_name) #header,
+                    }
+                });
+                names.push(variant_name);
+                conversions.push(quote! { Self::#variant_ident });
+            },
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                subcommands.push(quote! {
+                    <#ty>::__rawcmd_command::<C>(#variant_name, resolver) #header
+                });
+                names.push(variant_name);
+                conversions.push(quote! { Self::#variant_ident(<#ty>::__rawcmd_from_intent(intent, index)?) });
+            },
+            _ => {
+                return Err(syn::Error::new_spanned(variant, "only unit and single-field tuple variants are supported"));
+            },
+        }
+    }
+
+    let header = build_command_header(&attrs);
+    let api = build_public_api(ident);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #api
+
+            #[doc(hidden)]
+            pub fn __rawcmd_command<C: 'static>(
+                name: &str,
+                resolver: &Option<::std::rc::Rc<dyn Fn(&::rawcmd::Intent, &mut C) -> ::rawcmd::Result<i32>>>,
+            ) -> ::rawcmd::Command<C> {
+                Self::__rawcmd_subcommands(resolver).into_iter()
+                    .fold(::rawcmd::Command::with_name(name) #header, |command, subcommand| command.with_subcommand(subcommand))
+            }
+
+            #[doc(hidden)]
+            pub fn __rawcmd_subcommands<C: 'static>(
+                resolver: &Option<::std::rc::Rc<dyn Fn(&::rawcmd::Intent, &mut C) -> ::rawcmd::Result<i32>>>,
+            ) -> Vec<::rawcmd::Command<C>> {
+                vec![#(#subcommands),*]
+            }
+
+            #[doc(hidden)]
+            pub fn __rawcmd_from_path(intent: &::rawcmd::Intent, index: usize) -> ::rawcmd::Result<Option<Self>> {
+                let path: Vec<&str> = intent.supcommands().iter()
+                    .chain(::std::iter::once(intent.command()))
+                    .map(|c| c.name().as_str())
+                    .collect();
+                match path.get(index).copied() {
+                    #(Some(#names) => Ok(Some(#conversions)),)*
+                    _ => Ok(None),
+                }
+            }
+
+            #[doc(hidden)]
+            pub fn __rawcmd_from_intent(intent: &::rawcmd::Intent, depth: usize) -> ::rawcmd::Result<Self> {
+                Self::__rawcmd_from_path(intent, depth + 1)?.ok_or_else(|| {
+                    let name = intent.supcommands().iter()
+                        .chain(::std::iter::once(intent.command()))
+                        .nth(depth)
+                        .map_or_else(|| #name.to_string(), |c| c.name().to_string());
+                    ::rawcmd::Error::new(::rawcmd::ErrorKind::MissingCommandResolver(name))
+                })
+            }
+
+            #[doc(hidden)]
+            pub fn __rawcmd_name() -> &'static str {
+                #name
+            }
+        }
+
+        impl #impl_generics ::rawcmd::FromIntent for #ident #ty_generics #where_clause {
+            fn from_intent(intent: &::rawcmd::Intent) -> ::rawcmd::Result<Self> {
+                Self::__rawcmd_from_intent(intent, 0)
+            }
+        }
+    })
+}
+
+/// Returns the public `command`, `command_with_resolver` and `from_intent`
+/// functions.
+fn build_public_api(ident: &Ident) -> TokenStream {
+    let doc_command = format!("Returns the command tree of `{}`.", ident);
+    let doc_resolver = format!("Returns the command tree of `{}` where every resolvable command converts the intent and calls `resolver`.", ident);
+    let doc_intent = format!("Converts the intent into `{}`.", ident);
+    quote! {
+        #[doc = #doc_command]
+        pub fn command<C: 'static>() -> ::rawcmd::Command<C> {
+            Self::__rawcmd_command(Self::__rawcmd_name(), &None)
+        }
+
+        #[doc = #doc_resolver]
+        pub fn command_with_resolver<C: 'static, F>(resolver: F) -> ::rawcmd::Command<C>
+            where
+            F: Fn(Self, &mut C) -> ::rawcmd::Result<i32> + 'static,
+        {
+            let resolver: ::std::rc::Rc<dyn Fn(&::rawcmd::Intent, &mut C) -> ::rawcmd::Result<i32>> =
+                ::std::rc::Rc::new(move |intent, ctx| resolver(Self::from_intent(intent)?, ctx));
+            Self::__rawcmd_command(Self::__rawcmd_name(), &Some(resolver))
+        }
+
+        #[doc = #doc_intent]
+        pub fn from_intent(intent: &::rawcmd::Intent) -> ::rawcmd::Result<Self> {
+            Self::__rawcmd_from_intent(intent, 0)
+        }
+    }
+}
+
+/// Returns the builder calls which set about, description, version and
+/// author.
+fn build_command_header(attrs: &CommandAttrs) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    if let Some(about) = &attrs.about {
+        tokens.extend(quote! { .with_about(#about) });
+    }
+    if let Some(description) = &attrs.description {
+        tokens.extend(quote! { .with_description(#description) });
+    }
+    if let Some(version) = &attrs.version {
+        tokens.extend(quote! { .with_version(#version) });
+    }
+    if let Some(author) = &attrs.author {
+        tokens.extend(quote! { .with_author(#author) });
+    }
+    tokens
+}
+
+/// Returns the statement which adds a flag to `command`.
+fn build_flag(name: &str, attrs: &FieldAttrs, ty: &Type) -> TokenStream {
+    let mut flag = quote! { ::rawcmd::Flag::with_name(#name) };
+    if let Some(alias) = &attrs.alias {
+        flag.extend(quote! { .with_alias(#alias) });
+    }
+    if let Some(description) = &attrs.description {
+        flag.extend(quote! { .with_description(#description) });
+    }
+    if let Some(default) = &attrs.default {
+        flag.extend(quote! { .with_default_value(#default) });
+    }
+    if let Some(env) = &attrs.env {
+        flag.extend(quote! { .with_env(#env) });
+    }
+    if attrs.count {
+        flag.extend(quote! { .count() });
+    } else if attrs.negatable {
+        flag.extend(quote! { .negatable() });
+    } else {
+        match shape(ty) {
+            Shape::Bool => (),
+            Shape::Option(_) => flag.extend(quote! { .accept_value() }),
+            Shape::Vec(_) => flag.extend(quote! { .accept_value().multiple() }),
+            Shape::Plain(_) if attrs.default.is_some() => flag.extend(quote! { .accept_value() }),
+            Shape::Plain(_) => flag.extend(quote! { .accept_value().required() }),
+        }
+    }
+    quote! { let command = command.with_flag(#flag); }
+}
+
+/// Returns the statement which adds a param to `command`.
+fn build_param(name: &str, attrs: &FieldAttrs, ty: &Type) -> TokenStream {
+    let mut param = quote! { ::rawcmd::Param::with_name(#name) };
+    if let Some(description) = &attrs.description {
+        param.extend(quote! { .with_description(#description) });
+    }
+    if let Some(default) = &attrs.default {
+        param.extend(quote! { .with_default_value(#default) });
+    }
+    match shape(ty) {
+        Shape::Option(_) => (),
+        Shape::Vec(_) => param.extend(quote! { .variadic() }),
+        _ if attrs.default.is_some() => (),
+        _ => param.extend(quote! { .required() }),
+    }
+    quote! { let command = command.with_param(#param); }
+}
+
+/// Returns the expression which reads a flag field from `intent`.
+fn convert_flag(name: &str, attrs: &FieldAttrs, ty: &Type) -> TokenStream {
+    let invalid = quote! { |_| ::rawcmd::Error::new(::rawcmd::ErrorKind::InvalidFlagValue(#name.to_string())) };
+    let missing = quote! { ::rawcmd::ErrorKind::MissingRequiredFlag(#name.to_string()) };
+    if attrs.count {
+        return quote! { ::std::convert::TryFrom::try_from(intent.flag_count(#name)).map_err(#invalid)? };
+    } else if attrs.negatable {
+        return match shape(ty) {
+            Shape::Option(_) => quote! { intent.flag(#name).and_then(|f| f.to_bool()) },
+            _ => quote! { intent.flag(#name).and_then(|f| f.to_bool()).unwrap_or(false) },
+        };
+    }
+    match shape(ty) {
+        Shape::Bool => quote! { intent.flag(#name).and_then(|f| f.to_bool()).unwrap_or(false) },
+        Shape::Vec(inner) => quote! { intent.flag_values::<#inner, _>(#name)? },
+        Shape::Option(inner) => {
+            let fallback = build_fallback(attrs, inner, &invalid, None);
+            quote! { intent.flag_value::<#inner, _>(#name)?.or(#fallback) }
+        },
+        Shape::Plain(ty) => {
+            let fallback = build_fallback(attrs, ty, &invalid, Some(missing));
+            quote! { intent.flag_value::<#ty, _>(#name)?.map_or_else(|| #fallback, Ok)? }
+        },
+    }
+}
+
+/// Returns the expression which reads a param field from `intent`.
+fn convert_param(name: &str, index: usize, attrs: &FieldAttrs, ty: &Type) -> TokenStream {
+    let invalid = quote! { |_| ::rawcmd::Error::new(::rawcmd::ErrorKind::InvalidParamValue(#index)) };
+    let missing = quote! { ::rawcmd::ErrorKind::MissingRequiredParam(#name.to_string()) };
+    match shape(ty) {
+        Shape::Vec(inner) => quote! { intent.param_values::<#inner, _>(#name)? },
+        Shape::Option(inner) => {
+            let fallback = build_fallback(attrs, inner, &invalid, None);
+            quote! { intent.param_value::<#inner, _>(#name)?.or(#fallback) }
+        },
+        _ => {
+            let fallback = build_fallback(attrs, ty, &invalid, Some(missing));
+            quote! { intent.param_value::<#ty, _>(#name)?.map_or_else(|| #fallback, Ok)? }
+        },
+    }
+}
+
+/// Returns the value used when a flag or param is not part of the intent
+/// (e.g. a flag of a parent command). With `missing` the expression is a
+/// `Result` which fails with that error kind when there is no default,
+/// otherwise it is an `Option`.
+fn build_fallback(attrs: &FieldAttrs, ty: &Type, invalid: &TokenStream, missing: Option<TokenStream>) -> TokenStream {
+    match (&attrs.default, missing) {
+        (Some(default), Some(_)) => quote! { #default.parse::<#ty>().map_err(#invalid) },
+        (Some(default), None) => quote! { #default.parse::<#ty>().ok() },
+        (None, Some(missing)) => quote! { Err(::rawcmd::Error::new(#missing)) },
+        (None, None) => quote! { None },
+    }
+}
+
+/// Returns the shape of a field type.
+fn shape(ty: &Type) -> Shape<'_> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "bool" && segment.arguments.is_empty() {
+                return Shape::Bool;
+            }
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let (1, Some(GenericArgument::Type(inner))) = (args.args.len(), args.args.first()) {
+                    if segment.ident == "Option" {
+                        return Shape::Option(inner);
+                    } else if segment.ident == "Vec" {
+                        return Shape::Vec(inner);
+                    }
+                }
+            }
+        }
+    }
+    Shape::Plain(ty)
+}
+
+/// Returns options of the `#[command(...)]` attribute with doc comments
+/// used as about and description.
+fn parse_command_attrs(attrs: &[Attribute]) -> syn::Result<CommandAttrs> {
+    let mut options = CommandAttrs::default();
+    let docs = parse_docs(attrs);
+    let mut paragraphs = docs.splitn(2, "\n\n");
+    options.about = paragraphs.next().filter(|s| !s.is_empty()).map(|s| s.replace('\n', " "));
+    options.description = paragraphs.next().map(String::from);
+    for attr in attrs.iter().filter(|a| a.path().is_ident("command")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("subcommand") {
+                options.subcommand = true;
+                return Ok(());
+            }
+            let value = meta.value()?.parse::<LitStr>()?.value();
+            if meta.path.is_ident("name") {
+                options.name = Some(value);
+            } else if meta.path.is_ident("about") {
+                options.about = Some(value);
+            } else if meta.path.is_ident("description") {
+                options.description = Some(value);
+            } else if meta.path.is_ident("version") {
+                options.version = Some(value);
+            } else if meta.path.is_ident("author") {
+                options.author = Some(value);
+            } else {
+                return Err(meta.error("unsupported command option"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Returns the role of a struct field from its attributes where fields
+/// without an attribute become flags.
+fn parse_field_role(ident: &Ident, attrs: &[Attribute]) -> syn::Result<FieldRole> {
+    let mut role = None;
+    let mut options = FieldAttrs {
+        description: Some(parse_docs(attrs).replace('\n', " ")).filter(|s| !s.is_empty()),
+        ..FieldAttrs::default()
+    };
+    for attr in attrs.iter() {
+        if attr.path().is_ident("command") {
+            if !parse_command_attrs(std::slice::from_ref(attr))?.subcommand {
+                return Err(syn::Error::new_spanned(attr, "expected `#[command(subcommand)]`"));
+            }
+            return Ok(FieldRole::Subcommand);
+        } else if !attr.path().is_ident("flag") && !attr.path().is_ident("param") {
+            continue;
+        }
+        let is_flag = attr.path().is_ident("flag");
+        role = Some(is_flag);
+        if let syn::Meta::Path(_) = attr.meta {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if is_flag && meta.path.is_ident("count") {
+                options.count = true;
+                return Ok(());
+            } else if is_flag && meta.path.is_ident("negatable") {
+                options.negatable = true;
+                return Ok(());
+            }
+            let value = meta.value()?.parse::<LitStr>()?.value();
+            if meta.path.is_ident("name") {
+                options.name = Some(value);
+            } else if is_flag && meta.path.is_ident("alias") {
+                options.alias = Some(value);
+            } else if meta.path.is_ident("description") {
+                options.description = Some(value);
+            } else if meta.path.is_ident("default") {
+                options.default = Some(value);
+            } else if is_flag && meta.path.is_ident("env") {
+                options.env = Some(value);
+            } else {
+                return Err(meta.error(format!("unsupported option of field `{}`", ident)));
+            }
+            Ok(())
+        })?;
+    }
+    match role {
+        Some(false) => Ok(FieldRole::Param(options)),
+        _ => Ok(FieldRole::Flag(options)),
+    }
+}
+
+/// Returns doc comments joined into lines with blank lines between
+/// paragraphs.
+fn parse_docs(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs.iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(meta) => match &meta.value {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Some(lit.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Converts `CamelCase` and `snake_case` identifiers into `kebab-case`.
+fn kebab_case(name: &str) -> String {
+    let mut result = String::new();
+    for (index, ch) in name.chars().enumerate() {
+        if ch.is_uppercase() {
+            if index > 0 {
+                result.push('-');
+            }
+            result.extend(ch.to_lowercase());
+        } else if ch == '_' {
+            result.push('-');
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
@@ -0,0 +1,115 @@
+use rawcmd::{Command, Context, ErrorKind, Intent, Result};
+
+/// Manages remotes.
+#[derive(Command, Debug, PartialEq)]
+struct Add {
+    /// Remote name.
+    #[param]
+    name: String,
+    #[param]
+    urls: Vec<String>,
+    #[flag(alias = "b", default = "main")]
+    branch: String,
+    #[flag(alias = "t", env = "APP_TAGS")]
+    tags: bool,
+    #[flag(negatable)]
+    fetch: Option<bool>,
+}
+
+/// Renames a remote.
+#[derive(Command, Debug, PartialEq)]
+struct Rename {
+    #[param]
+    from: String,
+    #[param]
+    to: Option<String>,
+}
+
+#[derive(Command, Debug, PartialEq)]
+enum Remote {
+    Add(Add),
+    /// Lists remotes.
+    #[command(name = "ls")]
+    List,
+    Rename(Rename),
+}
+
+#[derive(Command, Debug, PartialEq)]
+enum Subcommand {
+    Remote(Remote),
+}
+
+/// Example application.
+#[derive(Command, Debug, PartialEq)]
+#[command(name = "app", version = "1.0.0")]
+struct App {
+    #[flag(alias = "v", count)]
+    verbose: u8,
+    #[flag(env = "APP_DEPTH")]
+    depth: Option<u32>,
+    #[flag]
+    include: Vec<String>,
+    #[command(subcommand)]
+    command: Option<Subcommand>,
+}
+
+fn run(args: Vec<&str>) -> Result<App> {
+    let mut ctx: Option<App> = None;
+    App::command_with_resolver(|app, ctx: &mut Option<App>| {
+        *ctx = Some(app);
+        Ok(0)
+    }).run_args_with_vars(args, vec![("APP_DEPTH", "3"), ("APP_TAGS", "yes")], &mut ctx)?;
+    Ok(ctx.unwrap())
+}
+
+#[test]
+fn builds_command_tree() {
+    let command = App::command::<Context>();
+    assert_eq!(command.name(), "app");
+    assert_eq!(command.version(), &Some("1.0.0".to_string()));
+    assert_eq!(command.flags().iter().map(|f| f.name().as_str()).collect::<Vec<_>>(), ["verbose", "depth", "include"]);
+    assert!(command.flags()[0].is_count());
+    assert!(command.flags()[2].is_multiple());
+    let add = &command.commands()[0].commands()[0];
+    assert_eq!(add.name(), "add");
+    assert_eq!(add.about(), &Some("Manages remotes.".to_string()));
+    assert!(add.params()[0].is_required());
+    assert!(add.params()[1].is_variadic());
+    assert_eq!(add.params()[0].description(), &Some("Remote name.".to_string()));
+    assert_eq!(command.commands()[0].commands()[1].name(), "ls");
+}
+
+#[test]
+fn converts_intent() {
+    assert_eq!(run(vec!["-vv", "--include", "a", "--include", "b"]).unwrap(), App {
+        verbose: 2,
+        depth: Some(3),
+        include: vec!["a".to_string(), "b".to_string()],
+        command: None,
+    });
+    assert_eq!(run(vec!["remote", "add", "origin", "x", "y", "-t"]).unwrap().command, Some(Subcommand::Remote(Remote::Add(Add {
+        name: "origin".to_string(),
+        urls: vec!["x".to_string(), "y".to_string()],
+        branch: "main".to_string(),
+        tags: true,
+        fetch: None,
+    }))));
+    let command = run(vec!["remote", "add", "origin", "--no-fetch"]).unwrap().command;
+    assert!(matches!(command, Some(Subcommand::Remote(Remote::Add(Add { fetch: Some(false), tags: true, .. })))));
+    assert_eq!(run(vec!["remote", "ls"]).unwrap().command, Some(Subcommand::Remote(Remote::List)));
+    assert_eq!(run(vec!["remote", "rename", "origin"]).unwrap().command, Some(Subcommand::Remote(Remote::Rename(Rename {
+        from: "origin".to_string(),
+        to: None,
+    }))));
+}
+
+#[test]
+fn reports_conversion_errors() {
+    fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+        intent.extract::<App>().map(|_| 0)
+    }
+    let err = App::command().with_resolver(resolver).run_args(vec!["--depth", "x"], &mut Context::default()).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidFlagValue("depth".to_string()));
+    let err = App::command_with_resolver(|_, _: &mut Context| Ok(0)).run_args(vec!["remote"], &mut Context::default()).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingCommandResolver("remote".to_string()));
+}
@@ -0,0 +1,3 @@
+use crate::{Intent, Result};
+
+pub type AfterHook<C> = Box<dyn Fn(&Intent, Result<i32>, &mut C) -> Result<i32>>;
@@ -0,0 +1,3 @@
+use crate::{BoxFuture, Intent, Result};
+
+pub type AsyncAfterHook<C> = Box<dyn for<'a> Fn(&'a Intent, Result<i32>, &'a mut C) -> BoxFuture<'a, Result<i32>>>;
@@ -0,0 +1,3 @@
+use crate::{BoxFuture, Intent, Result};
+
+pub type AsyncBeforeHook<C> = Box<dyn for<'a> Fn(&'a Intent, &'a mut C) -> BoxFuture<'a, Result<Option<i32>>>>;
@@ -0,0 +1,3 @@
+use crate::{BoxFuture, Error, Intent, Result};
+
+pub type AsyncCommandHandler<C> = Box<dyn for<'a> Fn(Error, Option<&'a Intent>, &'a mut C) -> BoxFuture<'a, Result<i32>>>;
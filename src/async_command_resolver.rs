@@ -0,0 +1,3 @@
+use crate::{BoxFuture, Intent, Result};
+
+pub type AsyncCommandResolver<C> = Box<dyn for<'a> Fn(&'a Intent, &'a mut C) -> BoxFuture<'a, Result<i32>>>;
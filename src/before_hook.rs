@@ -0,0 +1,3 @@
+use crate::{Intent, Result};
+
+pub type BeforeHook<C> = Box<dyn Fn(&Intent, &mut C) -> Result<Option<i32>>>;
@@ -1,22 +1,47 @@
+use std::env;
+use std::collections::HashMap;
 use crate::{Context, Result, Error, ErrorKind, CommandResolver, CommandHandler,
-    Flag, Param, Resource, Intent, build_subcommand_positions,
+    BeforeHook, AfterHook,
+    Flag, Param, Resource, Intent, Tail, build_tail, build_subcommand_positions,
     build_command_summary, subcommand_at_position, build_supcommand_summaries,
     build_subcommand_summaries, build_flag_summaries, build_param_summaries,
-    build_resource_summaries, parse_args};
+    build_resource_summaries, parse_args, build_help_positions, render_help_with_path, find_version_request,
+    render_version, Config, load_config, extract_config_path, active_config_flag,
+    build_config_values, find_conflicting_flag};
+#[cfg(feature = "async")]
+use crate::{BoxFuture, AsyncCommandResolver, AsyncCommandHandler, AsyncBeforeHook, AsyncAfterHook};
 
 /// Command structure which represents command-line task.
 pub struct Command<C = Context> {
     name: String,
+    aliases: Vec<String>,
     about: Option<String>,
     description: Option<String>,
     author: Option<String>,
     version: Option<String>,
+    version_flag: Option<Flag>,
+    config_name: Option<String>,
+    config_flag: Option<Flag>,
     flags: Vec<Flag>,
     params: Vec<Param>,
     resources: Vec<Resource>,
+    tail: Option<Tail>,
+    suggestion_distance: Option<usize>,
+    prefixes: bool,
+    conflicting_flag: Option<String>,
     commands: Vec<Command<C>>,
     handler: Option<CommandHandler<C>>,
     resolver: Option<CommandResolver<C>>,
+    before: Vec<BeforeHook<C>>,
+    after: Vec<AfterHook<C>>,
+    #[cfg(feature = "async")]
+    async_handler: Option<AsyncCommandHandler<C>>,
+    #[cfg(feature = "async")]
+    async_resolver: Option<AsyncCommandResolver<C>>,
+    #[cfg(feature = "async")]
+    async_before: Vec<AsyncBeforeHook<C>>,
+    #[cfg(feature = "async")]
+    async_after: Vec<AsyncAfterHook<C>>,
 }
 
 /// Command structure implementation.
@@ -27,6 +52,11 @@ impl<C> Command<C> {
         &self.name
     }
 
+    /// Returns alternative names.
+    pub fn aliases(&self) -> &Vec<String> {
+        &self.aliases
+    }
+
     /// Returns about.
     pub fn about(&self) -> &Option<String> {
         &self.about
@@ -47,6 +77,21 @@ impl<C> Command<C> {
         &self.version
     }
 
+    /// Returns version flag.
+    pub fn version_flag(&self) -> &Option<Flag> {
+        &self.version_flag
+    }
+
+    /// Returns name which is used to locate configuration files.
+    pub fn config_name(&self) -> &Option<String> {
+        &self.config_name
+    }
+
+    /// Returns config path flag.
+    pub fn config_flag(&self) -> &Option<Flag> {
+        &self.config_flag
+    }
+
     /// Returns flags.
     pub fn flags(&self) -> &Vec<Flag> {
         &self.flags
@@ -62,6 +107,23 @@ impl<C> Command<C> {
         &self.resources
     }
 
+    /// Returns policy for arguments after `--`.
+    pub fn tail(&self) -> &Option<Tail> {
+        &self.tail
+    }
+
+    /// Returns maximum edit distance of suggestions for unknown commands and
+    /// flags.
+    pub fn suggestion_distance(&self) -> Option<usize> {
+        self.suggestion_distance
+    }
+
+    /// Returns true when unambiguous prefixes of subcommand and long flag
+    /// names are accepted.
+    pub fn accepts_prefixes(&self) -> bool {
+        self.prefixes
+    }
+
     /// Returns commands.
     pub fn commands(&self) -> &Vec<Command<C>> {
         &self.commands
@@ -75,16 +137,42 @@ impl<C> Command<C> {
     pub fn with_name<S: Into<String>>(name: S) -> Self {
         Self {
             name: name.into(),
+            aliases: Vec::new(),
             about: None,
             flags: Vec::new(),
             params: Vec::new(),
             resources: Vec::new(),
+            tail: None,
+            suggestion_distance: None,
+            prefixes: false,
+            conflicting_flag: None,
             commands: Vec::new(),
             handler: None,
             resolver: None,
+            before: Vec::new(),
+            after: Vec::new(),
+            #[cfg(feature = "async")]
+            async_handler: None,
+            #[cfg(feature = "async")]
+            async_resolver: None,
+            #[cfg(feature = "async")]
+            async_before: Vec::new(),
+            #[cfg(feature = "async")]
+            async_after: Vec::new(),
             description: None,
             author: None,
             version: None,
+            version_flag: Some(
+                Flag::with_name("version")
+                    .with_alias("V")
+                    .with_description("Prints version information.")
+            ),
+            config_name: None,
+            config_flag: Some(
+                Flag::with_name("config")
+                    .accept_value()
+                    .with_description("Path to the configuration file.")
+            ),
         }
     }
 
@@ -94,6 +182,12 @@ impl<C> Command<C> {
         self
     }
 
+    /// Adds alternative name which resolves to this command.
+    pub fn with_alias<S: Into<String>>(mut self, val: S) -> Self {
+        self.aliases.push(val.into());
+        self
+    }
+
     /// Sets description.
     pub fn with_description<S: Into<String>>(mut self, val: S) -> Self {
         self.description = Some(val.into());
@@ -111,21 +205,90 @@ impl<C> Command<C> {
         self.version = Some(val.into());
         self
     }
+
+    /// Sets the flag which prints version (defaults to `--version` and `-V`).
+    pub fn with_version_flag(mut self, flag: Flag) -> Self {
+        self.version_flag = Some(flag);
+        self
+    }
+
+    /// Disables the automatic version flag.
+    pub fn without_version_flag(mut self) -> Self {
+        self.version_flag = None;
+        self
+    }
     
-    /// Sets error handler function.
-    pub fn with_handler(mut self, handler: CommandHandler<C>) -> Self {
-        self.handler = Some(handler);
+    /// Enables configuration files where `name` locates the file in the XDG
+    /// config directory (`$XDG_CONFIG_HOME/<name>/config.toml`) and in the
+    /// current directory (`.<name>.toml`).
+    pub fn with_config_name<S: Into<String>>(mut self, val: S) -> Self {
+        self.config_name = Some(val.into());
+        self
+    }
+
+    /// Sets the flag which provides an explicit config path (defaults to
+    /// `--config`).
+    pub fn with_config_flag(mut self, flag: Flag) -> Self {
+        self.config_flag = Some(flag);
+        self
+    }
+
+    /// Disables the config path flag.
+    pub fn without_config_flag(mut self) -> Self {
+        self.config_flag = None;
+        self
+    }
+
+    /// Sets error handler function or closure. The handler receives errors of
+    /// the command and of descendants without a handler, together with the
+    /// intent when parsing succeeded.
+    pub fn with_handler<F>(mut self, handler: F) -> Self
+        where
+        F: Fn(Error, Option<&Intent>, &mut C) -> Result<i32> + 'static,
+    {
+        self.handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets resolver function or closure.
+    pub fn with_resolver<F>(mut self, resolver: F) -> Self
+        where
+        F: Fn(&Intent, &mut C) -> Result<i32> + 'static,
+    {
+        self.resolver = Some(Box::new(resolver));
         self
     }
 
-    /// Sets resolver function.
-    pub fn with_resolver(mut self, resolver: CommandResolver<C>) -> Self {
-        self.resolver = Some(resolver);
+    /// Adds a hook which runs before the resolver of this command and of all
+    /// its descendants. Hooks run from the root to the leaf and a hook which
+    /// returns an exit code or an error skips the rest of them and the
+    /// resolver.
+    pub fn with_before<F>(mut self, hook: F) -> Self
+        where
+        F: Fn(&Intent, &mut C) -> Result<Option<i32>> + 'static,
+    {
+        self.before.push(Box::new(hook));
+        self
+    }
+
+    /// Adds a hook which runs after the resolver of this command and of all
+    /// its descendants. Hooks run from the leaf to the root, receive the
+    /// result of the resolver (or of the previous hook) and return the result
+    /// which is passed on. They run for every command whose before hooks
+    /// passed.
+    pub fn with_after<F>(mut self, hook: F) -> Self
+        where
+        F: Fn(&Intent, Result<i32>, &mut C) -> Result<i32> + 'static,
+    {
+        self.after.push(Box::new(hook));
         self
     }
 
     /// Adds flag.
     pub fn with_flag(mut self, flag: Flag) -> Self {
+        if flag.is_global() && self.conflicting_flag.is_none() {
+            self.conflicting_flag = self.commands.iter().find_map(|c| find_conflicting_flag(c, &flag));
+        }
         self.flags.push(flag);
         self
     }
@@ -142,8 +305,36 @@ impl<C> Command<C> {
         self
     }
 
+    /// Sets policy for arguments after `--`. Commands without a policy
+    /// accept a tail without listing it in usage lines.
+    pub fn with_tail(mut self, tail: Tail) -> Self {
+        self.tail = Some(tail);
+        self
+    }
+
+    /// Sets maximum edit distance of suggestions for unknown commands and
+    /// flags (defaults to 2) where `0` disables suggestions. Subcommands
+    /// inherit the setting unless they set their own.
+    pub fn with_suggestion_distance(mut self, val: usize) -> Self {
+        self.suggestion_distance = Some(val);
+        self
+    }
+
+    /// Enables matching subcommands and long flags by any unambiguous
+    /// prefix of their names (e.g. `stat` for `status`). Subcommands inherit
+    /// the setting.
+    pub fn accept_prefixes(mut self) -> Self {
+        self.prefixes = true;
+        self
+    }
+
     /// Adds subcommand.
     pub fn with_subcommand(mut self, command: Command<C>) -> Self {
+        if self.conflicting_flag.is_none() {
+            self.conflicting_flag = command.conflicting_flag.clone().or_else(|| {
+                self.flags.iter().filter(|f| f.is_global()).find_map(|f| find_conflicting_flag(&command, f))
+            });
+        }
         self.commands.push(command);
         self
     }
@@ -158,18 +349,134 @@ impl<C> Command<C> {
         where
         A: IntoIterator<Item = T>,
         T: Into<String>,
+    {
+        self.run_args_with_vars(args, env::vars(), ctx)
+    }
+
+    /// Executes as a command-line application where environment variables
+    /// are read from `vars` instead of the process environment.
+    pub fn run_args_with_vars<A, T, V, K, W>(mut self, args: A, vars: V, ctx: &mut C) -> Result<i32>
+        where
+        A: IntoIterator<Item = T>,
+        T: Into<String>,
+        V: IntoIterator<Item = (K, W)>,
+        K: Into<String>,
+        W: Into<String>,
+    {
+        let mut positions = Vec::new();
+        let intent = match self.prepare_intent(args, vars, &mut positions) {
+            Ok(Some(intent)) => intent,
+            Ok(None) => return Ok(0),
+            Err(err) => return self.handle_error(err, &positions, None, ctx),
+        };
+
+        let chain = self.command_chain(&positions);
+        let mut entered = 0;
+        let mut result = None;
+        for command in chain.iter() {
+            result = command.run_before_hooks(&intent, ctx);
+            if result.is_some() {
+                break;
+            }
+            entered += 1;
+        }
+
+        let command = chain[chain.len() - 1];
+        let mut result = result.unwrap_or_else(|| command.resolve(&intent, ctx));
+        for command in chain[..entered].iter().rev() {
+            result = command.run_after_hooks(&intent, result, ctx);
+        }
+        match result {
+            Ok(code) => Ok(code),
+            Err(err) => self.handle_error(err, &positions, Some(&intent), ctx),
+        }
+    }
+
+    /// Collects arguments and environment variables, applies inherited
+    /// settings and parses the intent of the requested command where
+    /// `positions` receive its position in a tree once known.
+    fn prepare_intent<A, T, V, K, W>(&mut self, args: A, vars: V, positions: &mut Vec<usize>) -> Result<Option<Intent>>
+        where
+        A: IntoIterator<Item = T>,
+        T: Into<String>,
+        V: IntoIterator<Item = (K, W)>,
+        K: Into<String>,
+        W: Into<String>,
     {
         let args: Vec<String> = args.into_iter().map(Into::into).collect();
-        let command_positions = build_subcommand_positions(&self, &args)?;
-        let command = subcommand_at_position(&self, &command_positions);
-        let command_summary = build_command_summary(&command);
-        let supcommand_summaries = build_supcommand_summaries(&self, &command_positions);
-        let subcommand_summaries = build_subcommand_summaries(&command);
-        let flag_summaries = build_flag_summaries(&command, &args)?;
-        let param_summaries = build_param_summaries(&self, &args)?;
-        let resource_summaries = build_resource_summaries(&command);
-
-        let intent = Intent::new(
+        let vars: HashMap<String, String> = vars.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        if let Some(name) = &self.conflicting_flag {
+            return Err(Error::new(ErrorKind::ConflictingGlobalFlag(name.to_string())));
+        }
+        self.inherit_settings();
+        self.build_intent(args, &vars, positions)
+    }
+
+    /// Runs before hooks of the command and returns the result of the first
+    /// hook which stops the execution.
+    fn run_before_hooks(&self, intent: &Intent, ctx: &mut C) -> Option<Result<i32>> {
+        for hook in self.before.iter() {
+            match hook(intent, ctx) {
+                Ok(None) => continue,
+                Ok(Some(code)) => return Some(Ok(code)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+
+    /// Runs after hooks of the command in reverse order of registration.
+    fn run_after_hooks(&self, intent: &Intent, mut result: Result<i32>, ctx: &mut C) -> Result<i32> {
+        for hook in self.after.iter().rev() {
+            result = hook(intent, result, ctx);
+        }
+        result
+    }
+
+    /// Runs the resolver of the command.
+    fn resolve(&self, intent: &Intent, ctx: &mut C) -> Result<i32> {
+        match &self.resolver {
+            Some(resolver) => resolver(intent, ctx),
+            None => Err(Error::new(ErrorKind::MissingCommandResolver(self.name().to_string()))),
+        }
+    }
+
+    /// Parses arguments into the intent of the requested command where
+    /// `positions` receive its position in a tree once known. Returns `None`
+    /// when the arguments were answered by printing help or version.
+    fn build_intent(&self, args: Vec<String>, vars: &HashMap<String, String>, positions: &mut Vec<usize>) -> Result<Option<Intent>> {
+        let (items, config_path) = extract_config_path(self, args.clone())?;
+        if let Some(positions) = build_help_positions(self, &items)? {
+            let command = subcommand_at_position(self, &positions);
+            let path: Vec<String> = build_supcommand_summaries(self, &positions).iter()
+                .map(|s| s.name().to_string())
+                .collect();
+            println!("{}", render_help_with_path(command, &path));
+            return Ok(None);
+        }
+
+        *positions = build_subcommand_positions(self, &items)?;
+        let command = subcommand_at_position(self, positions);
+        if let Some(long) = find_version_request(self, &items) {
+            println!("{}", render_version(command, long));
+            return Ok(None);
+        }
+
+        let config = match self.config_name() {
+            Some(name) => load_config(name, config_path, vars)?,
+            None => Config::default(),
+        };
+
+        let command_summary = build_command_summary(command);
+        let supcommand_summaries = build_supcommand_summaries(self, positions);
+        let subcommand_summaries = build_subcommand_summaries(command);
+        let config_values = build_config_values(&config, &self.command_chain(positions));
+        let flag_summaries = build_flag_summaries(command, &items, vars, &config_values)?;
+        let param_summaries = build_param_summaries(self, &items)?;
+        let resource_summaries = build_resource_summaries(command);
+        let tail = build_tail(command, &items)?;
+
+        Ok(Some(Intent::new(
             args,
             command_summary,
             supcommand_summaries,
@@ -177,20 +484,182 @@ impl<C> Command<C> {
             flag_summaries,
             param_summaries,
             resource_summaries,
-        );
+        ).with_tail(tail)))
+    }
 
-        let err = match &command.resolver {
-            Some(resolver) => match resolver(&intent, ctx) {
-                Ok(code) => return Ok(code),
-                Err(err) => err,
-            },
-            None => Error::new(ErrorKind::MissingCommandResolver(command.name().to_string())),
-        };
-        match &command.handler {
-            Some(handler) => handler(err, ctx),
+    /// Passes an error to the handler of the command at `positions` or to the
+    /// handler of its nearest ancestor.
+    fn handle_error(&self, err: Error, positions: &[usize], intent: Option<&Intent>, ctx: &mut C) -> Result<i32> {
+        let handler = self.command_chain(positions).into_iter().rev().find_map(|c| c.handler.as_ref());
+        match handler {
+            Some(handler) => handler(err, intent, ctx),
             None => Err(err),
         }
     }
+
+    /// Returns commands from the root to the command at `positions`.
+    fn command_chain(&self, positions: &[usize]) -> Vec<&Command<C>> {
+        let mut chain = vec![self];
+        for position in positions.iter().copied() {
+            chain.push(&chain[chain.len() - 1].commands[position]);
+        }
+        chain
+    }
+
+    /// Passes settings and global flags which apply to the whole tree down to
+    /// subcommands. The tree is expected to have no conflicting global flags.
+    fn inherit_settings(&mut self) {
+        let globals: Vec<Flag> = self.flags.iter().filter(|f| f.is_global()).cloned().collect();
+        let config_flag = active_config_flag(self);
+        for command in self.commands.iter_mut() {
+            if command.suggestion_distance.is_none() {
+                command.suggestion_distance = self.suggestion_distance;
+            }
+            command.prefixes = command.prefixes || self.prefixes;
+            if command.config_name.is_none() && self.config_name.is_some() {
+                command.config_name = self.config_name.clone();
+                command.config_flag = config_flag.clone();
+            }
+            command.flags.extend(globals.iter().cloned());
+            command.inherit_settings();
+        }
+    }
+}
+
+/// Command structure implementation of the asynchronous API.
+#[cfg(feature = "async")]
+impl<C> Command<C> {
+
+    /// Sets asynchronous error handler which takes precedence over the
+    /// handler set through `with_handler` when running asynchronously.
+    pub fn with_async_handler<F>(mut self, handler: F) -> Self
+        where
+        F: for<'a> Fn(Error, Option<&'a Intent>, &'a mut C) -> BoxFuture<'a, Result<i32>> + 'static,
+    {
+        self.async_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets asynchronous resolver which takes precedence over the resolver
+    /// set through `with_resolver` when running asynchronously.
+    pub fn with_async_resolver<F>(mut self, resolver: F) -> Self
+        where
+        F: for<'a> Fn(&'a Intent, &'a mut C) -> BoxFuture<'a, Result<i32>> + 'static,
+    {
+        self.async_resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Adds asynchronous before hook which runs after the command's
+    /// synchronous before hooks.
+    pub fn with_async_before<F>(mut self, hook: F) -> Self
+        where
+        F: for<'a> Fn(&'a Intent, &'a mut C) -> BoxFuture<'a, Result<Option<i32>>> + 'static,
+    {
+        self.async_before.push(Box::new(hook));
+        self
+    }
+
+    /// Adds asynchronous after hook which runs before the command's
+    /// synchronous after hooks.
+    pub fn with_async_after<F>(mut self, hook: F) -> Self
+        where
+        F: for<'a> Fn(&'a Intent, Result<i32>, &'a mut C) -> BoxFuture<'a, Result<i32>> + 'static,
+    {
+        self.async_after.push(Box::new(hook));
+        self
+    }
+
+    /// Executes as a command-line application and returns a future which can
+    /// be driven by any executor. Synchronous resolvers, handlers and hooks
+    /// are used where no asynchronous counterpart is set.
+    pub async fn run_async(self, ctx: &mut C) -> Result<i32> {
+        self.run_args_async(parse_args(), ctx).await
+    }
+
+    /// Executes as a command-line application asynchronously.
+    pub async fn run_args_async<A, T>(self, args: A, ctx: &mut C) -> Result<i32>
+        where
+        A: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.run_args_with_vars_async(args, env::vars(), ctx).await
+    }
+
+    /// Executes as a command-line application asynchronously where
+    /// environment variables are read from `vars` instead of the process
+    /// environment.
+    pub async fn run_args_with_vars_async<A, T, V, K, W>(mut self, args: A, vars: V, ctx: &mut C) -> Result<i32>
+        where
+        A: IntoIterator<Item = T>,
+        T: Into<String>,
+        V: IntoIterator<Item = (K, W)>,
+        K: Into<String>,
+        W: Into<String>,
+    {
+        let mut positions = Vec::new();
+        let intent = match self.prepare_intent(args, vars, &mut positions) {
+            Ok(Some(intent)) => intent,
+            Ok(None) => return Ok(0),
+            Err(err) => return self.handle_error_async(err, &positions, None, ctx).await,
+        };
+
+        let chain = self.command_chain(&positions);
+        let mut entered = 0;
+        let mut result = None;
+        for command in chain.iter() {
+            result = match command.run_before_hooks(&intent, ctx) {
+                Some(result) => Some(result),
+                None => command.run_async_before_hooks(&intent, ctx).await,
+            };
+            if result.is_some() {
+                break;
+            }
+            entered += 1;
+        }
+
+        let command = chain[chain.len() - 1];
+        let mut result = match (result, &command.async_resolver) {
+            (Some(result), _) => result,
+            (None, Some(resolver)) => resolver(&intent, ctx).await,
+            (None, None) => command.resolve(&intent, ctx),
+        };
+        for command in chain[..entered].iter().rev() {
+            for hook in command.async_after.iter().rev() {
+                result = hook(&intent, result, ctx).await;
+            }
+            result = command.run_after_hooks(&intent, result, ctx);
+        }
+        match result {
+            Ok(code) => Ok(code),
+            Err(err) => self.handle_error_async(err, &positions, Some(&intent), ctx).await,
+        }
+    }
+
+    /// Runs asynchronous before hooks of the command and returns the result
+    /// of the first hook which stops the execution.
+    async fn run_async_before_hooks(&self, intent: &Intent, ctx: &mut C) -> Option<Result<i32>> {
+        for hook in self.async_before.iter() {
+            match hook(intent, ctx).await {
+                Ok(None) => continue,
+                Ok(Some(code)) => return Some(Ok(code)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+
+    /// Passes an error to the nearest asynchronous or synchronous handler.
+    async fn handle_error_async(&self, err: Error, positions: &[usize], intent: Option<&Intent>, ctx: &mut C) -> Result<i32> {
+        for command in self.command_chain(positions).into_iter().rev() {
+            if let Some(handler) = &command.async_handler {
+                return handler(err, intent, ctx).await;
+            } else if let Some(handler) = &command.handler {
+                return handler(err, intent, ctx);
+            }
+        }
+        Err(err)
+    }
 }
 
 #[cfg(test)]
@@ -207,6 +676,24 @@ mod tests {
         assert_eq!(app.run_args(vec![] as Vec<String>, &mut ctx), Ok(2));
     }
 
+    #[test]
+    fn resolves_closures_with_captured_state() {
+        let mut app = Command::with_name("a");
+        for (name, code) in [("b", 1), ("c", 2)] {
+            let prefix = format!("{}-", name);
+            app = app.with_subcommand(
+                Command::with_name(name)
+                    .with_resolver(move |intent: &Intent, _: &mut Context| {
+                        Ok(code + intent.tail().iter().filter(|a| a.starts_with(&prefix)).count() as i32)
+                    })
+                    .with_handler(move |_, _, _| Ok(code * 10))
+            );
+        }
+        let mut ctx = Context::default();
+        let app = app.with_resolver(|_, _| Ok(0));
+        assert_eq!(app.run_args(vec!["c", "--", "c-1", "b-1"], &mut ctx), Ok(3));
+    }
+
     #[test]
     fn resolves_subcommand() {
         fn resolver0(_: &Intent, _: &mut Context) -> Result<i32> { Ok(1) };
@@ -218,12 +705,364 @@ mod tests {
         assert_eq!(app.run_args(vec!["b"], &mut ctx), Ok(1));
     }
 
+    #[test]
+    fn resolves_global_flags() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            let verbose = intent.flag("verbose").unwrap().count() as i32;
+            let output: i32 = intent.flag_value("output").unwrap().unwrap();
+            let param: i32 = intent.param_value("target").unwrap().unwrap();
+            Ok(verbose * 100 + output * 10 + param)
+        }
+        let mut ctx = Context::default();
+        let build = || Command::with_name("a")
+            .with_flag(Flag::with_name("verbose").with_alias("v").count().global())
+            .with_flag(Flag::with_name("output").with_alias("o").accept_value().global())
+            .with_subcommand(
+                Command::with_name("b").with_subcommand(
+                    Command::with_name("c").with_param(Param::with_name("target")).with_resolver(resolver)
+                )
+            );
+        assert_eq!(build().run_args(vec!["-v", "--output", "2", "b", "-v", "c", "3"], &mut ctx), Ok(223));
+        assert_eq!(build().run_args(vec!["b", "c", "3", "-o", "4"], &mut ctx), Ok(43));
+        let app = build().with_subcommand(Command::with_name("d").with_flag(Flag::with_name("x").with_alias("o")));
+        assert_eq!(
+            app.run_args(vec!["b", "c", "3"], &mut ctx).unwrap_err().kind(),
+            &ErrorKind::ConflictingGlobalFlag("output".to_string()),
+        );
+    }
+
+    #[test]
+    fn detects_conflicting_global_flags() {
+        let mut ctx = Context::default();
+        let app = Command::<Context>::with_name("a")
+            .with_subcommand(Command::with_name("b").with_subcommand(Command::with_name("c").with_flag(Flag::with_name("output"))))
+            .with_flag(Flag::with_name("output").global())
+            .with_resolver(|_, _| Ok(0));
+        assert!(crate::render_help(&app).contains("GLOBAL FLAGS:"));
+        assert_eq!(
+            app.run_args(Vec::<String>::new(), &mut ctx).unwrap_err().kind(),
+            &ErrorKind::ConflictingGlobalFlag("output".to_string()),
+        );
+        let app = Command::<Context>::with_name("a")
+            .with_subcommand(
+                Command::with_name("b")
+                    .with_flag(Flag::with_name("verbose").with_alias("v").global())
+                    .with_subcommand(Command::with_name("c").with_flag(Flag::with_name("version").with_alias("v")))
+            )
+            .with_resolver(|_, _| Ok(0));
+        assert_eq!(
+            app.run_args(Vec::<String>::new(), &mut ctx).unwrap_err().kind(),
+            &ErrorKind::ConflictingGlobalFlag("verbose".to_string()),
+        );
+        let app = Command::<Context>::with_name("a")
+            .with_flag(Flag::with_name("verbose").global())
+            .with_subcommand(Command::with_name("b").with_flag(Flag::with_name("quiet")))
+            .with_resolver(|_, _| Ok(0));
+        assert_eq!(app.run_args(Vec::<String>::new(), &mut ctx), Ok(0));
+    }
+
+    #[test]
+    fn resolves_negatable_flags() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            match intent.flag("color").unwrap().to_bool() {
+                Some(true) => Ok(1),
+                Some(false) => Ok(0),
+                None => Ok(-1),
+            }
+        }
+        let mut ctx = Context::default();
+        let build = |flag: Flag| Command::with_name("a")
+            .with_flag(flag.negatable().with_env("COLOR"))
+            .with_resolver(resolver);
+        let run = |flag: Flag, args: Vec<&str>, vars: Vec<(&str, &str)>, ctx: &mut Context| build(flag).run_args_with_vars(args, vars, ctx);
+        assert_eq!(run(Flag::with_name("color"), vec![], vec![], &mut ctx), Ok(-1));
+        assert_eq!(run(Flag::with_name("color"), vec!["--color"], vec![], &mut ctx), Ok(1));
+        assert_eq!(run(Flag::with_name("color"), vec!["--no-color"], vec![], &mut ctx), Ok(0));
+        assert_eq!(run(Flag::with_name("color").with_default_value("true"), vec![], vec![], &mut ctx), Ok(1));
+        assert_eq!(run(Flag::with_name("color").with_default_value("true"), vec!["--no-color"], vec![], &mut ctx), Ok(0));
+        assert_eq!(run(Flag::with_name("color"), vec!["--color"], vec![("COLOR", "false")], &mut ctx), Ok(1));
+        assert_eq!(run(Flag::with_name("color"), vec![], vec![("COLOR", "false")], &mut ctx), Ok(0));
+        assert_eq!(run(Flag::with_name("color").multiple(), vec!["--color", "--no-color"], vec![], &mut ctx), Ok(0));
+        let err = run(Flag::with_name("color"), vec!["--no-colr"], vec![], &mut ctx).unwrap_err();
+        assert_eq!(err.suggestions(), &["--no-color"]);
+        let err = run(Flag::with_name("color").accept_value(), vec!["--no-color"], vec![], &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnknownFlag("--no-color".to_string()));
+    }
+
+    #[test]
+    fn validates_choices() {
+        fn resolver(_: &Intent, _: &mut Context) -> Result<i32> { Ok(1) }
+        let mut ctx = Context::default();
+        let build = || Command::with_name("a")
+            .with_flag(Flag::with_name("format").accept_value().with_choices(vec!["json", "yaml", "table"]).with_env("FORMAT"))
+            .with_param(Param::with_name("mode").with_choices(vec!["fast", "slow"]))
+            .with_resolver(resolver);
+        assert_eq!(build().run_args(vec!["--format", "yaml", "fast"], &mut ctx), Ok(1));
+        let err = build().run_args(vec!["--format", "yml"], &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidFlagValue("format".to_string()));
+        assert_eq!(err.suggestions(), &["yaml"]);
+        assert_eq!(err.message(), "The provided flag `format` has invalid value. Did you mean `yaml`? Possible values are `json`, `yaml` or `table`.");
+        let err = build().run_args_with_vars(vec![] as Vec<String>, vec![("FORMAT", "xml")], &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidFlagValue("format".to_string()));
+        let err = build().run_args(vec!["quick"], &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidParamValue(0));
+        assert!(err.suggestions().is_empty());
+    }
+
+    #[test]
+    fn reads_injected_vars() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            Ok(intent.flag("aaa").unwrap().to_value::<i32>().unwrap())
+        }
+        let mut ctx = Context::default();
+        let app = Command::with_name("a")
+            .with_flag(Flag::with_name("aaa").accept_value().with_env("AAA"))
+            .with_resolver(resolver);
+        assert_eq!(app.run_args_with_vars(vec![] as Vec<String>, vec![("AAA", "5")], &mut ctx), Ok(5));
+    }
+
+    #[test]
+    fn reads_config_files() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            Ok(intent.flag("aaa").unwrap().to_value::<i32>().unwrap())
+        }
+        let dir = env::temp_dir().join(format!("rawcmd-config-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("app")).unwrap();
+        std::fs::write(dir.join("app").join("config.toml"), "aaa = 1\n[b]\naaa = 2\n").unwrap();
+        std::fs::write(dir.join("explicit.toml"), "[b]\naaa = 3\n").unwrap();
+        let build = || Command::with_name("a")
+            .with_config_name("app")
+            .with_flag(Flag::with_name("aaa").accept_value().with_env("AAA"))
+            .with_subcommand(
+                Command::with_name("b")
+                    .with_flag(Flag::with_name("aaa").accept_value().with_env("AAA").with_default_value("0"))
+                    .with_resolver(resolver)
+            )
+            .with_resolver(resolver);
+        let xdg = dir.to_str().unwrap().to_string();
+        let explicit = dir.join("explicit.toml").to_str().unwrap().to_string();
+        let mut ctx = Context::default();
+        assert_eq!(build().run_args_with_vars(vec![] as Vec<String>, vec![("XDG_CONFIG_HOME", xdg.as_str())], &mut ctx), Ok(1));
+        assert_eq!(build().run_args_with_vars(vec!["b"], vec![("XDG_CONFIG_HOME", xdg.as_str())], &mut ctx), Ok(2));
+        assert_eq!(build().run_args_with_vars(vec!["b", "--config", &explicit], vec![("XDG_CONFIG_HOME", xdg.as_str())], &mut ctx), Ok(3));
+        assert_eq!(build().run_args_with_vars(vec!["b"], vec![("XDG_CONFIG_HOME", xdg.as_str()), ("AAA", "4")], &mut ctx), Ok(4));
+        assert_eq!(build().run_args_with_vars(vec!["b", "--aaa", "5"], vec![("XDG_CONFIG_HOME", xdg.as_str()), ("AAA", "4")], &mut ctx), Ok(5));
+        assert_eq!(build().run_args_with_vars(vec!["b"], Vec::<(String, String)>::new(), &mut ctx), Ok(0));
+        let broken = dir.join("broken.toml").to_str().unwrap().to_string();
+        std::fs::write(&broken, "aaa\n").unwrap();
+        assert_eq!(build().run_args_with_vars(vec!["b", "--help", "--config", &broken], Vec::<(String, String)>::new(), &mut ctx), Ok(0));
+        assert!(build().run_args_with_vars(vec!["b", "--config", &broken], Vec::<(String, String)>::new(), &mut ctx).is_err());
+        let mut command = build();
+        command.inherit_settings();
+        assert!(crate::render_help(&command.commands()[0]).contains("GLOBAL FLAGS:\n        --config"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_config_path_in_intent_args() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            assert_eq!(intent.args().len(), 4);
+            assert_eq!(intent.args()[1], "--config");
+            assert!(intent.flag("aaa").unwrap().provided());
+            Ok(1)
+        }
+        let dir = env::temp_dir().join(format!("rawcmd-args-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("x.toml"), "").unwrap();
+        let app = Command::with_name("a")
+            .with_config_name("app")
+            .with_subcommand(Command::with_name("b").with_flag(Flag::with_name("aaa")).with_resolver(resolver));
+        let mut ctx = Context::default();
+        let result = app.run_args(vec!["b", "--config", dir.join("x.toml").to_str().unwrap(), "--aaa"], &mut ctx);
+        std::fs::remove_dir_all(dir).unwrap();
+        assert_eq!(result, Ok(1));
+    }
+
+    #[test]
+    fn reads_tail() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            Ok(intent.tail().len() as i32)
+        }
+        let mut ctx = Context::default();
+        let app = Command::with_name("a")
+            .with_subcommand(Command::with_name("b").with_tail(Tail::Required).with_resolver(resolver))
+            .with_subcommand(Command::with_name("c").with_tail(Tail::Forbidden).with_resolver(resolver))
+            .with_resolver(resolver);
+        assert_eq!(app.run_args(vec!["--", "x", "--y"], &mut ctx), Ok(2));
+        let app = Command::with_name("a")
+            .with_subcommand(Command::with_name("b").with_tail(Tail::Required).with_resolver(resolver))
+            .with_subcommand(Command::with_name("c").with_tail(Tail::Forbidden).with_resolver(resolver));
+        assert_eq!(app.run_args(vec!["b"], &mut ctx).unwrap_err().kind(), &ErrorKind::MissingTail("b".to_string()));
+    }
+
+    #[test]
+    fn suggests_similar_names() {
+        fn resolver(_: &Intent, _: &mut Context) -> Result<i32> { Ok(1) }
+        let mut ctx = Context::default();
+        let build = || Command::with_name("a")
+            .with_subcommand(
+                Command::with_name("status")
+                    .with_flag(Flag::with_name("verbose"))
+                    .with_resolver(resolver)
+            )
+            .with_subcommand(Command::with_name("stash").with_resolver(resolver));
+        let err = build().run_args(vec!["stats"], &mut ctx).unwrap_err();
+        assert_eq!(err.suggestions(), &["status", "stash"]);
+        let err = build().run_args(vec!["status", "--verbos"], &mut ctx).unwrap_err();
+        assert_eq!(err.suggestions(), &["--verbose"]);
+        let err = build().with_suggestion_distance(0).run_args(vec!["status", "--verbos"], &mut ctx).unwrap_err();
+        assert!(err.suggestions().is_empty());
+    }
+
+    #[test]
+    fn resolves_aliases_and_prefixes() {
+        fn resolver(intent: &Intent, _: &mut Context) -> Result<i32> {
+            Ok(intent.command().name().len() as i32 + intent.flags().iter().filter(|f| f.provided()).count() as i32 * 10)
+        }
+        let mut ctx = Context::default();
+        let build = || Command::with_name("a")
+            .with_subcommand(
+                Command::with_name("remove")
+                    .with_alias("rm")
+                    .with_flag(Flag::with_name("force"))
+                    .with_resolver(resolver)
+            )
+            .with_subcommand(Command::with_name("rename").with_resolver(resolver));
+        assert_eq!(build().run_args(vec!["rm"], &mut ctx).unwrap(), 6);
+        assert_eq!(build().run_args(vec!["remo"], &mut ctx).unwrap_err().kind(), &ErrorKind::UnknownCommand("remo".to_string()));
+        assert_eq!(build().accept_prefixes().run_args(vec!["remo", "--fo"], &mut ctx).unwrap(), 16);
+        assert_eq!(build().accept_prefixes().run_args(vec!["ren"], &mut ctx).unwrap(), 6);
+        let err = build().accept_prefixes().run_args(vec!["re"], &mut ctx).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::AmbiguousCommand("re".to_string()));
+        assert_eq!(err.message(), "The requested command `re` is ambiguous. Did you mean `remove` or `rename`?");
+    }
+
+    #[test]
+    fn runs_hooks() {
+        #[derive(Default)]
+        struct Trace { items: Vec<String> }
+        fn resolver(_: &Intent, ctx: &mut Trace) -> Result<i32> {
+            ctx.items.push("resolve".to_string());
+            Ok(1)
+        }
+        let build = || Command::<Trace>::with_name("a")
+            .with_subcommand(
+                Command::<Trace>::with_name("b")
+                    .with_before(|_, ctx| { ctx.items.push("before b".to_string()); Ok(None) })
+                    .with_after(|_, result, ctx| { ctx.items.push("after b".to_string()); result.map(|c| c + 1) })
+                    .with_resolver(resolver)
+            )
+            .with_subcommand(
+                Command::with_name("c")
+                    .with_before(|intent, _| Ok(intent.tail().first().map(|_| 5)))
+                    .with_resolver(resolver)
+            )
+            .with_before(|_, ctx| { ctx.items.push("before a".to_string()); Ok(None) })
+            .with_after(|_, result, ctx| { ctx.items.push("after a".to_string()); result.map(|c| c * 10) });
+
+        let mut ctx = Trace::default();
+        assert_eq!(build().run_args(vec!["b"], &mut ctx), Ok(20));
+        assert_eq!(ctx.items, ["before a", "before b", "resolve", "after b", "after a"]);
+
+        let mut ctx = Trace::default();
+        assert_eq!(build().run_args(vec!["c", "--", "x"], &mut ctx), Ok(50));
+        assert_eq!(ctx.items, ["before a", "after a"]);
+    }
+
+    #[cfg(feature = "async")]
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        use std::task::{RawWaker, RawWakerVTable, Waker};
+        fn raw_waker() -> RawWaker {
+            fn clone(_: *const ()) -> RawWaker { raw_waker() }
+            fn noop(_: *const ()) {}
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        let waker = unsafe { Waker::from_raw(raw_waker()) };
+        let mut future = Box::pin(future);
+        let mut cx = std::task::Context::from_waker(&waker);
+        loop {
+            if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn resolves_command_async() {
+        #[derive(Default)]
+        struct Trace { items: Vec<String> }
+        let build = || Command::<Trace>::with_name("a")
+            .with_subcommand(
+                Command::<Trace>::with_name("b")
+                    .with_async_resolver(|intent, ctx| Box::pin(async move {
+                        ctx.items.push("resolve".to_string());
+                        Ok(intent.tail().len() as i32)
+                    }))
+                    .with_after(|_, result, ctx| { ctx.items.push("after".to_string()); result })
+            )
+            .with_subcommand(Command::with_name("c").with_resolver(|_, _| Err(Error::default())))
+            .with_async_before(|_, ctx| Box::pin(async move {
+                ctx.items.push("before".to_string());
+                Ok(None)
+            }))
+            .with_async_handler(|_, intent, _| Box::pin(async move { Ok(intent.map_or(0, |_| 7)) }));
+
+        let mut ctx = Trace::default();
+        assert_eq!(block_on(build().run_args_async(vec!["b", "--", "x"], &mut ctx)), Ok(1));
+        assert_eq!(ctx.items, ["before", "resolve", "after"]);
+        assert_eq!(block_on(build().run_args_async(vec!["c"], &mut ctx)), Ok(7));
+        assert_eq!(block_on(build().run_args_async(vec!["x"], &mut ctx)), Ok(0));
+    }
+
     #[test]
     fn handles_error() {
         fn resolver(_: &Intent, _: &mut Context) -> Result<i32> { Err(Error::default()) }
-        fn handler(_error: Error, _: &mut Context) -> Result<i32> { Ok(1) }
+        fn handler(_error: Error, _: Option<&Intent>, _: &mut Context) -> Result<i32> { Ok(1) }
         let mut ctx = Context::default();
         let app = Command::with_name("a").with_resolver(resolver).with_handler(handler);
         assert_eq!(app.run_args(vec![] as Vec<String>, &mut ctx), Ok(1));
     }
+
+    #[test]
+    fn bubbles_error_to_ancestor_handler() {
+        fn resolver(_: &Intent, _: &mut Context) -> Result<i32> { Err(Error::default()) }
+        fn handler(_: Error, intent: Option<&Intent>, _: &mut Context) -> Result<i32> {
+            Ok(intent.map_or(0, |i| i.supcommands().len() as i32 + 1))
+        }
+        let build = || Command::with_name("a")
+            .with_subcommand(
+                Command::with_name("b")
+                    .with_subcommand(Command::with_name("c").with_resolver(resolver))
+                    .with_handler(|_, _, _| Ok(10))
+            )
+            .with_subcommand(Command::with_name("d").with_resolver(resolver))
+            .with_handler(handler);
+        let mut ctx = Context::default();
+        assert_eq!(build().run_args(vec!["d"], &mut ctx), Ok(2));
+        assert_eq!(build().run_args(vec!["b", "c"], &mut ctx), Ok(10));
+        assert_eq!(build().run_args(vec!["d", "--x"], &mut ctx), Ok(0));
+        assert_eq!(build().run_args(vec!["x"], &mut ctx), Ok(0));
+    }
+
+    #[test]
+    fn intercepts_help() {
+        fn resolver(_: &Intent, _: &mut Context) -> Result<i32> { Ok(1) }
+        let mut ctx = Context::default();
+        let app = Command::with_name("a")
+            .with_subcommand(Command::with_name("b").with_resolver(resolver))
+            .with_resolver(resolver);
+        assert_eq!(app.run_args(vec!["b", "--help"], &mut ctx), Ok(0));
+    }
+
+    #[test]
+    fn intercepts_version() {
+        fn resolver(_: &Intent, _: &mut Context) -> Result<i32> { Ok(1) }
+        let mut ctx = Context::default();
+        let app = Command::with_name("a").with_version("1.0.0").with_resolver(resolver);
+        assert_eq!(app.run_args(vec!["-V"], &mut ctx), Ok(0));
+        let app = Command::with_name("a").with_resolver(resolver);
+        assert!(app.run_args(vec!["-V"], &mut ctx).is_err());
+    }
 }
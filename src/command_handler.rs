@@ -1,3 +1,3 @@
-use crate::{Error, Result};
+use crate::{Error, Intent, Result};
 
-pub type CommandHandler<C> = fn(err: Error, context: &mut C) -> Result<i32>;
+pub type CommandHandler<C> = Box<dyn Fn(Error, Option<&Intent>, &mut C) -> Result<i32>>;
@@ -1,3 +1,3 @@
 use crate::{Intent, Result};
 
-pub type CommandResolver<C> = fn(intent: &Intent, context: &mut C) -> Result<i32>;
+pub type CommandResolver<C> = Box<dyn Fn(&Intent, &mut C) -> Result<i32>>;
@@ -1,5 +1,6 @@
 /// Structure with command summary.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CommandSummary {
     name: String,
     about: Option<String>,
@@ -0,0 +1,462 @@
+use crate::{Command, Flag, Shell, build_visible_flags, build_global_flags};
+
+/// Node of a command tree prepared for completion where `commands` pairs
+/// subcommand names and aliases with the names they resolve to and `values`
+/// holds choices of params.
+struct CompletionNode {
+    path: String,
+    commands: Vec<(String, String)>,
+    flags: Vec<Flag>,
+    values: Vec<String>,
+}
+
+/// Returns a static completion script of a command tree for a shell.
+pub fn render_completions<C>(app: &Command<C>, shell: Shell) -> String {
+    let mut nodes = Vec::new();
+    build_completion_nodes(app, app.name(), &[], &mut nodes);
+    match shell {
+        Shell::Bash => render_bash(app.name(), &nodes),
+        Shell::Zsh => render_zsh(app.name(), &nodes),
+        Shell::Fish => render_fish(app.name(), &nodes),
+    }
+}
+
+/// Collects completion nodes of a command and its descendants where
+/// `globals` holds global flags of parent commands.
+fn build_completion_nodes<C>(command: &Command<C>, path: &str, globals: &[Flag], nodes: &mut Vec<CompletionNode>) {
+    let mut flags = build_visible_flags(command);
+    flags.extend(globals.iter().cloned());
+    nodes.push(CompletionNode {
+        path: path.to_string(),
+        commands: command.commands().iter().flat_map(|c| {
+            std::iter::once(c.name()).chain(c.aliases().iter()).map(move |w| (w.to_string(), c.name().to_string()))
+        }).collect(),
+        flags,
+        values: command.params().iter().flat_map(|p| p.choices().iter().cloned()).collect(),
+    });
+    let mut globals = globals.to_vec();
+    globals.extend(build_global_flags(command));
+    for subcommand in command.commands().iter() {
+        build_completion_nodes(subcommand, &format!("{} {}", path, subcommand.name()), &globals, nodes);
+    }
+}
+
+/// Returns command-line spellings of a flag (e.g. `--flag`, `--no-flag` and
+/// `-f`).
+fn flag_words(flag: &Flag) -> Vec<String> {
+    let mut words = vec![format!("--{}", flag.name())];
+    if flag.is_negatable() {
+        words.push(format!("--no-{}", flag.name()));
+    }
+    if let Some(alias) = flag.alias() {
+        words.push(format!("-{}", alias));
+    }
+    words
+}
+
+/// Returns names and aliases of a node's subcommands.
+fn command_words(node: &CompletionNode) -> Vec<String> {
+    node.commands.iter().map(|(word, _)| word.to_string()).collect()
+}
+
+/// Returns case patterns which move the cursor into subcommands.
+fn command_patterns(nodes: &[CompletionNode]) -> Vec<(String, String)> {
+    nodes.iter().flat_map(|node| {
+        node.commands.iter().map(move |(word, name)| {
+            (format!("\"1:{}:{}\"", node.path, word), format!("{} {}", node.path, name))
+        })
+    }).collect()
+}
+
+/// Returns case patterns which match global flags preceding subcommands
+/// together with whether the flag takes a value.
+fn global_flag_patterns(nodes: &[CompletionNode]) -> Vec<(String, bool)> {
+    nodes.iter().filter(|node| !node.commands.is_empty()).flat_map(|node| {
+        node.flags.iter().filter(|f| f.is_global()).map(move |flag| {
+            let pattern = flag_words(flag).iter()
+                .map(|w| format!("\"1:{}:{}\"", node.path, w))
+                .collect::<Vec<String>>()
+                .join("|");
+            (pattern, flag.accepts_value())
+        })
+    }).collect()
+}
+
+/// Returns case patterns which match value-taking flags of each node.
+fn value_flag_patterns(nodes: &[CompletionNode]) -> Vec<String> {
+    nodes.iter().flat_map(|node| {
+        node.flags.iter().filter(|f| f.accepts_value()).map(move |flag| {
+            flag_words(flag).iter()
+                .map(|w| format!("\"{}:{}\"", node.path, w))
+                .collect::<Vec<String>>()
+                .join("|")
+        })
+    }).collect()
+}
+
+/// Returns case patterns which match value-taking flags with choices together
+/// with the choices separated by spaces.
+fn choice_flag_patterns(nodes: &[CompletionNode]) -> Vec<(String, String)> {
+    nodes.iter().flat_map(|node| {
+        node.flags.iter().filter(|f| f.accepts_value() && !f.choices().is_empty()).map(move |flag| {
+            let pattern = flag_words(flag).iter()
+                .map(|w| format!("\"{}:{}\"", node.path, w))
+                .collect::<Vec<String>>()
+                .join("|");
+            (pattern, flag.choices().join(" "))
+        })
+    }).collect()
+}
+
+/// Returns a shell function name for a command name.
+fn function_name(name: &str) -> String {
+    let name: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("_{}", name)
+}
+
+/// Returns completion script for Bash.
+fn render_bash(name: &str, nodes: &[CompletionNode]) -> String {
+    let function = function_name(name);
+    let mut lines = vec![
+        format!("{}() {{", function),
+        "    local cur prev word cmd_path cmds cmd_names flag_names value_names candidates i".to_string(),
+        "    COMPREPLY=()".to_string(),
+        "    cur=\"${COMP_WORDS[COMP_CWORD]}\"".to_string(),
+        "    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"".to_string(),
+        format!("    cmd_path=\"{}\"", name),
+        "    cmds=1".to_string(),
+        "    i=1".to_string(),
+        "    while [ \"$i\" -lt \"$COMP_CWORD\" ]; do".to_string(),
+        "        word=\"${COMP_WORDS[i]}\"".to_string(),
+        "        case \"${cmds}:${cmd_path}:${word}\" in".to_string(),
+    ];
+    for (pattern, path) in command_patterns(nodes) {
+        lines.push(format!("            {}) cmd_path=\"{}\" ;;", pattern, path));
+    }
+    for (pattern, value) in global_flag_patterns(nodes) {
+        match value {
+            true => lines.push(format!("            {}) i=$((i + 1)) ;;", pattern)),
+            false => lines.push(format!("            {}) ;;", pattern)),
+        }
+    }
+    lines.push("            *)".to_string());
+    lines.push("                cmds=0".to_string());
+    lines.push("                case \"${cmd_path}:${word}\" in".to_string());
+    for pattern in value_flag_patterns(nodes) {
+        lines.push(format!("                    {}) i=$((i + 1)) ;;", pattern));
+    }
+    lines.push("                esac".to_string());
+    lines.push("                ;;".to_string());
+    lines.push("        esac".to_string());
+    lines.push("        i=$((i + 1))".to_string());
+    lines.push("    done".to_string());
+    lines.push("    case \"${cmd_path}:${prev}\" in".to_string());
+    for (pattern, choices) in choice_flag_patterns(nodes) {
+        lines.push(format!("        {}) COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\")); return 0 ;;", pattern, choices));
+    }
+    for pattern in value_flag_patterns(nodes) {
+        lines.push(format!("        {}) return 0 ;;", pattern));
+    }
+    lines.push("    esac".to_string());
+    lines.push("    value_names=\"\"".to_string());
+    lines.push("    case \"${cmd_path}\" in".to_string());
+    for node in nodes.iter() {
+        let flags: Vec<String> = node.flags.iter().flat_map(flag_words).collect();
+        let mut line = format!(
+            "        \"{}\") cmd_names=\"{}\"; flag_names=\"{}\"",
+            node.path, command_words(node).join(" "), flags.join(" "),
+        );
+        if !node.values.is_empty() {
+            line.push_str(&format!("; value_names=\"{}\"", node.values.join(" ")));
+        }
+        lines.push(line + " ;;");
+    }
+    lines.push("    esac".to_string());
+    lines.push("    if [ \"${cmds}\" = \"1\" ]; then".to_string());
+    lines.push("        candidates=\"${cmd_names} ${value_names} ${flag_names}\"".to_string());
+    lines.push("    else".to_string());
+    lines.push("        candidates=\"${value_names} ${flag_names}\"".to_string());
+    lines.push("    fi".to_string());
+    lines.push("    COMPREPLY=($(compgen -W \"${candidates}\" -- \"${cur}\"))".to_string());
+    lines.push("    return 0".to_string());
+    lines.push("}".to_string());
+    lines.push(String::new());
+    lines.push(format!("complete -o default -F {} {}", function, name));
+    lines.join("\n") + "\n"
+}
+
+/// Returns completion script for Zsh.
+fn render_zsh(name: &str, nodes: &[CompletionNode]) -> String {
+    let function = function_name(name);
+    let mut lines = vec![
+        format!("#compdef {}", name),
+        String::new(),
+        format!("{}() {{", function),
+        "    local word cmd_path cmds cmd_names flag_names value_names ret i".to_string(),
+        format!("    cmd_path=\"{}\"", name),
+        "    cmds=1".to_string(),
+        "    i=2".to_string(),
+        "    while [ \"$i\" -lt \"$CURRENT\" ]; do".to_string(),
+        "        word=\"${words[i]}\"".to_string(),
+        "        case \"${cmds}:${cmd_path}:${word}\" in".to_string(),
+    ];
+    for (pattern, path) in command_patterns(nodes) {
+        lines.push(format!("            {}) cmd_path=\"{}\" ;;", pattern, path));
+    }
+    for (pattern, value) in global_flag_patterns(nodes) {
+        match value {
+            true => lines.push(format!("            {}) i=$((i + 1)) ;;", pattern)),
+            false => lines.push(format!("            {}) ;;", pattern)),
+        }
+    }
+    lines.push("            *)".to_string());
+    lines.push("                cmds=0".to_string());
+    lines.push("                case \"${cmd_path}:${word}\" in".to_string());
+    for pattern in value_flag_patterns(nodes) {
+        lines.push(format!("                    {}) i=$((i + 1)) ;;", pattern));
+    }
+    lines.push("                esac".to_string());
+    lines.push("                ;;".to_string());
+    lines.push("        esac".to_string());
+    lines.push("        i=$((i + 1))".to_string());
+    lines.push("    done".to_string());
+    lines.push("    case \"${cmd_path}:${words[CURRENT-1]}\" in".to_string());
+    for (pattern, choices) in choice_flag_patterns(nodes) {
+        lines.push(format!("        {}) compadd -- {}; return ;;", pattern, choices));
+    }
+    for pattern in value_flag_patterns(nodes) {
+        lines.push(format!("        {}) _default; return ;;", pattern));
+    }
+    lines.push("    esac".to_string());
+    lines.push("    cmd_names=()".to_string());
+    lines.push("    flag_names=()".to_string());
+    lines.push("    value_names=()".to_string());
+    lines.push("    case \"${cmd_path}\" in".to_string());
+    for node in nodes.iter() {
+        let flags: Vec<String> = node.flags.iter().flat_map(flag_words).collect();
+        let mut line = format!(
+            "        \"{}\") cmd_names=({}); flag_names=({})",
+            node.path, command_words(node).join(" "), flags.join(" "),
+        );
+        if !node.values.is_empty() {
+            line.push_str(&format!("; value_names=({})", node.values.join(" ")));
+        }
+        lines.push(line + " ;;");
+    }
+    lines.push("    esac".to_string());
+    lines.push("    ret=1".to_string());
+    lines.push("    if [ \"${cmds}\" = \"1\" ]; then".to_string());
+    lines.push("        compadd -- \"${cmd_names[@]}\" && ret=0".to_string());
+    lines.push("    fi".to_string());
+    lines.push("    compadd -- \"${value_names[@]}\" && ret=0".to_string());
+    lines.push("    compadd -- \"${flag_names[@]}\" && ret=0".to_string());
+    lines.push("    [ \"${ret}\" = \"0\" ] || _default".to_string());
+    lines.push("}".to_string());
+    lines.push(String::new());
+    lines.push(format!("if [ \"$funcstack[1]\" = \"{}\" ]; then", function));
+    lines.push(format!("    {} \"$@\"", function));
+    lines.push("else".to_string());
+    lines.push(format!("    compdef {} {}", function, name));
+    lines.push("fi".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// Returns completion script for Fish.
+fn render_fish(name: &str, nodes: &[CompletionNode]) -> String {
+    let function = format!("_{}_cmd_path", function_name(name));
+    let mut lines = vec![
+        format!("function {}", function),
+        "    set -l cmd_path \"".to_string() + name + "\"",
+        "    set -l cmds 1".to_string(),
+        "    set -l skip 0".to_string(),
+        "    for word in (commandline -opc)[2..-1]".to_string(),
+        "        if test $skip = 1".to_string(),
+        "            set skip 0".to_string(),
+        "            continue".to_string(),
+        "        end".to_string(),
+        "        switch \"$cmds:$cmd_path:$word\"".to_string(),
+    ];
+    for (pattern, path) in command_patterns(nodes) {
+        lines.push(format!("            case {}", pattern));
+        lines.push(format!("                set cmd_path \"{}\"", path));
+    }
+    for (pattern, value) in global_flag_patterns(nodes) {
+        lines.push(format!("            case {}", pattern.replace('|', " ")));
+        if value {
+            lines.push("                set skip 1".to_string());
+        }
+    }
+    lines.push("            case '*'".to_string());
+    lines.push("                set cmds 0".to_string());
+    lines.push("                switch \"$cmd_path:$word\"".to_string());
+    for pattern in value_flag_patterns(nodes) {
+        lines.push(format!("                    case {}", pattern.replace('|', " ")));
+        lines.push("                        set skip 1".to_string());
+    }
+    lines.push("                end".to_string());
+    lines.push("        end".to_string());
+    lines.push("    end".to_string());
+    lines.push("    echo \"$cmds:$cmd_path\"".to_string());
+    lines.push("end".to_string());
+    lines.push(String::new());
+    for node in nodes.iter() {
+        for (command, _) in node.commands.iter() {
+            lines.push(format!(
+                "complete -c {} -n '{} | string match -q \"1:{}\"' -a {}",
+                name, function, node.path, command,
+            ));
+        }
+        if !node.values.is_empty() {
+            lines.push(format!(
+                "complete -c {} -n '{} | string match -q \"*:{}\"' -a '{}'",
+                name, function, node.path, node.values.join(" "),
+            ));
+        }
+        for flag in node.flags.iter() {
+            let mut line = format!(
+                "complete -c {} -n '{} | string match -q \"*:{}\"' -l {}",
+                name, function, node.path, flag.name(),
+            );
+            if let Some(alias) = flag.alias() {
+                match alias.chars().count() {
+                    1 => line.push_str(&format!(" -s {}", alias)),
+                    _ => line.push_str(&format!(" -o {}", alias)),
+                }
+            }
+            if flag.accepts_value() && !flag.choices().is_empty() {
+                line.push_str(&format!(" -x -a '{}'", flag.choices().join(" ")));
+            } else if flag.accepts_value() {
+                line.push_str(" -r");
+            }
+            if let Some(description) = flag.description() {
+                line.push_str(&format!(" -d '{}'", description.replace('\\', "\\\\").replace('\'', "\\'")));
+            }
+            lines.push(line);
+            if flag.is_negatable() {
+                lines.push(format!(
+                    "complete -c {} -n '{} | string match -q \"*:{}\"' -l no-{}",
+                    name, function, node.path, flag.name(),
+                ));
+            }
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Param};
+
+    fn build_app() -> Command<Context> {
+        Command::with_name("app")
+            .with_flag(Flag::with_name("output").with_alias("o").accept_value())
+            .with_subcommand(
+                Command::with_name("remote")
+                    .with_flag(Flag::with_name("force").with_alias("f").with_description("Force it"))
+                    .with_subcommand(Command::with_name("add"))
+            )
+    }
+
+    #[test]
+    fn renders_fish_completions() {
+        let script = render_completions(&build_app(), Shell::Fish);
+        assert!(script.contains("            case \"1:app:remote\"\n                set cmd_path \"app remote\"\n"));
+        assert!(script.contains("                    case \"app:--output\" \"app:-o\"\n                        set skip 1\n"));
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"1:app\"' -a remote\n"));
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"*:app\"' -l output -s o -r\n"));
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"*:app remote\"' -l force -s f -d 'Force it'\n"));
+    }
+
+    #[test]
+    fn renders_zsh_completions() {
+        let script = render_completions(&build_app(), Shell::Zsh);
+        assert!(script.starts_with("#compdef app\n"));
+        assert!(script.contains("        \"app\") cmd_names=(remote); flag_names=(--output -o --help -h) ;;\n"));
+        assert!(script.contains("        \"app remote add\") cmd_names=(); flag_names=(--help -h) ;;\n"));
+        assert!(script.contains("        \"app:--output\"|\"app:-o\") _default; return ;;\n"));
+    }
+
+    #[test]
+    fn renders_bash_completions() {
+        let script = render_completions(&build_app(), Shell::Bash);
+        assert!(script.contains("            \"1:app:remote\") cmd_path=\"app remote\" ;;\n"));
+        assert!(script.contains("            \"1:app remote:add\") cmd_path=\"app remote add\" ;;\n"));
+        assert!(script.contains("        \"app remote\") cmd_names=\"add\"; flag_names=\"--force -f --help -h\" ;;\n"));
+        assert!(script.ends_with("complete -o default -F _app app\n"));
+    }
+
+    #[test]
+    fn renders_command_aliases() {
+        let app = Command::<Context>::with_name("app")
+            .with_subcommand(Command::with_name("remove").with_alias("rm"));
+        let script = render_completions(&app, Shell::Bash);
+        assert!(script.contains("            \"1:app:rm\") cmd_path=\"app remove\" ;;\n"));
+        assert!(script.contains("        \"app\") cmd_names=\"remove rm\"; flag_names=\"--help -h\" ;;\n"));
+        let script = render_completions(&app, Shell::Fish);
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"1:app\"' -a rm\n"));
+    }
+
+    #[test]
+    fn renders_global_flags() {
+        let app = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("output").accept_value().global())
+            .with_subcommand(Command::with_name("remote"));
+        let script = render_completions(&app, Shell::Bash);
+        assert!(script.contains("            \"1:app:--output\") i=$((i + 1)) ;;\n"));
+        assert!(script.contains("        \"app remote\") cmd_names=\"\"; flag_names=\"--help -h --output\" ;;\n"));
+        let script = render_completions(&app, Shell::Fish);
+        assert!(script.contains("            case \"1:app:--output\"\n                set skip 1\n"));
+    }
+
+    #[test]
+    fn renders_negatable_flags() {
+        let app = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("color").negatable());
+        let script = render_completions(&app, Shell::Bash);
+        assert!(script.contains("        \"app\") cmd_names=\"\"; flag_names=\"--color --no-color --help -h\" ;;\n"));
+        let script = render_completions(&app, Shell::Fish);
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"*:app\"' -l no-color\n"));
+    }
+
+    #[test]
+    fn renders_choices() {
+        let app = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("format").accept_value().with_choices(vec!["json", "yaml"]))
+            .with_param(Param::with_name("mode").with_choices(vec!["fast", "slow"]));
+        let script = render_completions(&app, Shell::Bash);
+        assert!(script.contains("        \"app:--format\") COMPREPLY=($(compgen -W \"json yaml\" -- \"${cur}\")); return 0 ;;\n"));
+        assert!(script.contains("        \"app\") cmd_names=\"\"; flag_names=\"--format --help -h\"; value_names=\"fast slow\" ;;\n"));
+        let script = render_completions(&app, Shell::Zsh);
+        assert!(script.contains("        \"app:--format\") compadd -- json yaml; return ;;\n"));
+        let script = render_completions(&app, Shell::Fish);
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"*:app\"' -l format -x -a 'json yaml'\n"));
+        assert!(script.contains("complete -c app -n '__app_cmd_path | string match -q \"*:app\"' -a 'fast slow'\n"));
+    }
+
+    #[test]
+    fn completes_with_bash() {
+        let script = render_completions(&build_app(), Shell::Bash);
+        let complete = |words: &str| -> Option<String> {
+            let source = format!(
+                "{}\nCOMP_WORDS=({})\nCOMP_CWORD=$((${{#COMP_WORDS[@]}} - 1))\n_app\necho \"${{COMPREPLY[*]}}\"",
+                script, words,
+            );
+            let output = std::process::Command::new("bash").arg("-c").arg(source).output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        };
+        if complete("app ''").is_none() {
+            return; // bash is not available
+        }
+        assert_eq!(complete("app ''").unwrap(), "remote --output -o --help -h");
+        assert_eq!(complete("app re").unwrap(), "remote");
+        assert_eq!(complete("app remote ''").unwrap(), "add --force -f --help -h");
+        assert_eq!(complete("app remote -f ''").unwrap(), "--force -f --help -h");
+        assert_eq!(complete("app remote add --").unwrap(), "--help");
+        assert_eq!(complete("app --output").unwrap(), "--output");
+        assert_eq!(complete("app --output ''").unwrap(), "");
+        assert_eq!(complete("app -o remote ''").unwrap(), "--output -o --help -h");
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{Result, Error, ErrorKind, Command, Flag};
+
+/// Structure which holds flag values loaded from configuration files. Values
+/// are grouped by command path (e.g. `remote.add`) where the root command is
+/// represented by an empty path.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Config {
+    sections: HashMap<String, HashMap<String, Vec<String>>>,
+}
+
+/// Structure implementation.
+impl Config {
+
+    /// Returns new instance from TOML-like or INI-style source.
+    pub fn parse<S: Into<String>>(source: S) -> Result<Self> {
+        let source = source.into();
+        let mut sections: HashMap<String, HashMap<String, Vec<String>>> = HashMap::new();
+        let mut section = String::new();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            } else if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+            let invalid = || Error::new(ErrorKind::InvalidConfig(format!("line {}", index + 1)));
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().map(|k| k.trim()).filter(|k| !k.is_empty()).ok_or_else(invalid)?;
+            let value = parts.next().map(|v| v.trim()).ok_or_else(invalid)?;
+            let values = parse_config_value(value).ok_or_else(invalid)?;
+            sections.entry(section.clone()).or_default().insert(key.to_string(), values);
+        }
+        Ok(Self { sections })
+    }
+
+    /// Returns new instance from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => return Err(Error::with_source(err, ErrorKind::InvalidConfig(path.display().to_string()))),
+        };
+        match Self::parse(source) {
+            Ok(config) => Ok(config),
+            Err(err) => match err.kind() {
+                ErrorKind::InvalidConfig(line) => Err(Error::new(ErrorKind::InvalidConfig(format!("{} ({})", path.display(), line)))),
+                _ => Err(err),
+            },
+        }
+    }
+
+    /// Returns values of a command path by key.
+    pub fn section<S: Into<String>>(&self, path: S) -> Option<&HashMap<String, Vec<String>>> {
+        self.sections.get(&path.into())
+    }
+
+    /// Returns values of a key in a command path.
+    pub fn values<S: Into<String>, K: Into<String>>(&self, path: S, key: K) -> Option<&Vec<String>> {
+        self.section(path).and_then(|s| s.get(&key.into()))
+    }
+
+    /// Returns a merged configuration where the existing values take
+    /// precedence over the values of `other`.
+    pub fn merge(mut self, other: Config) -> Self {
+        for (path, values) in other.sections.into_iter() {
+            let section = self.sections.entry(path).or_default();
+            for (key, value) in values.into_iter() {
+                section.entry(key).or_insert(value);
+            }
+        }
+        self
+    }
+}
+
+/// Returns merged configuration of the explicit file, the project-local file
+/// and the file in the XDG config directory, in this order of precedence.
+pub(crate) fn load_config(name: &str, explicit: Option<String>, vars: &HashMap<String, String>) -> Result<Config> {
+    let mut config = match explicit {
+        Some(path) => Config::from_file(path)?,
+        None => Config::default(),
+    };
+    let mut paths = vec![PathBuf::from(format!(".{}.toml", name))];
+    if let Some(dir) = vars.get("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        paths.push(PathBuf::from(dir).join(name).join("config.toml"));
+    } else if let Some(dir) = vars.get("HOME").filter(|d| !d.is_empty()) {
+        paths.push(PathBuf::from(dir).join(".config").join(name).join("config.toml"));
+    }
+    for path in paths.iter().filter(|p| p.is_file()) {
+        config = config.merge(Config::from_file(path)?);
+    }
+    Ok(config)
+}
+
+/// Returns configuration values of the last command in `chain` (from the root
+/// to the invoked command). A global flag falls back to the sections of the
+/// ancestors up to the command which declares it.
+pub(crate) fn build_config_values<C>(config: &Config, chain: &[&Command<C>]) -> HashMap<String, Vec<String>> {
+    let last = chain.len() - 1;
+    let path = |index: usize| chain[1..=index].iter()
+        .map(|c| c.name().to_string())
+        .collect::<Vec<String>>()
+        .join(".");
+    let mut values = HashMap::new();
+    for flag in chain[last].flags().iter() {
+        let first = match flag.is_global() {
+            true => chain.iter().position(|c| c.flags().iter().any(|f| f.name() == flag.name())).unwrap_or(last),
+            false => last,
+        };
+        if let Some(items) = (first..=last).rev().find_map(|index| config.values(path(index), flag.name())) {
+            values.insert(flag.name().to_string(), items.clone());
+        }
+    }
+    values
+}
+
+/// Returns the config path flag of a command with configuration files. The
+/// flag is accepted by every command in the tree thus it is global and it is
+/// dropped when any command declares a flag of the same name while a clashing
+/// alias only drops the alias.
+pub(crate) fn active_config_flag<C>(command: &Command<C>) -> Option<Flag> {
+    let flag = match (command.config_name(), command.config_flag()) {
+        (Some(_), Some(flag)) => flag.clone().global(),
+        _ => return None,
+    };
+    let mut flags = Vec::new();
+    collect_flags(command, &mut flags);
+    if flags.iter().any(|f| f.name() == flag.name()) {
+        return None;
+    }
+    match flags.iter().any(|f| f.alias().is_some() && f.alias() == flag.alias()) {
+        true => Some(flag.without_alias()),
+        false => Some(flag),
+    }
+}
+
+/// Collects flags of a command and its descendants.
+fn collect_flags<'a, C>(command: &'a Command<C>, flags: &mut Vec<&'a Flag>) {
+    flags.extend(command.flags().iter());
+    for subcommand in command.commands().iter() {
+        collect_flags(subcommand, flags);
+    }
+}
+
+/// Returns arguments without the config path flag together with the
+/// provided config path.
+pub(crate) fn extract_config_path<C>(app: &Command<C>, args: Vec<String>) -> Result<(Vec<String>, Option<String>)> {
+    let flag = match active_config_flag(app) {
+        Some(flag) => flag,
+        None => return Ok((args, None)),
+    };
+    let mut items = Vec::new();
+    let mut path = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            items.push(arg);
+            items.extend(args.by_ref());
+        } else if arg == format!("--{}", flag.name())
+            || flag.alias().is_some() && arg == format!("-{}", flag.alias().as_ref().unwrap())
+        {
+            match args.next() {
+                Some(value) if !value.starts_with('-') => path = Some(value),
+                _ => return Err(Error::new(ErrorKind::MissingFlagValue(arg))),
+            }
+        } else {
+            items.push(arg);
+        }
+    }
+    Ok((items, path))
+}
+
+/// Returns values of a config entry which is either a scalar or an array.
+fn parse_config_value(value: &str) -> Option<Vec<String>> {
+    if value.starts_with('[') && value.ends_with(']') {
+        let inner = value[1..value.len() - 1].trim();
+        if inner.is_empty() {
+            return Some(vec![]);
+        }
+        split_config_array(inner).iter().map(|v| parse_config_scalar(v.trim())).collect()
+    } else {
+        parse_config_scalar(value).map(|v| vec![v])
+    }
+}
+
+/// Returns a scalar config value without quotes and trailing comments.
+fn parse_config_scalar(value: &str) -> Option<String> {
+    let quote = value.chars().next()?;
+    if quote != '"' && quote != '\'' {
+        let value = value.split(" #").next().unwrap_or("").trim();
+        return Some(value.to_string());
+    }
+    let mut result = String::new();
+    let mut chars = value.chars().skip(1);
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if quote == '"' => match chars.next()? {
+                'n' => result.push('\n'),
+                't' => result.push('\t'),
+                ch => result.push(ch),
+            },
+            ch if ch == quote => {
+                let rest: String = chars.collect();
+                let rest = rest.trim();
+                return match rest.is_empty() || rest.starts_with('#') || rest.starts_with(';') {
+                    true => Some(result),
+                    false => None,
+                };
+            },
+            ch => result.push(ch),
+        }
+    }
+    None
+}
+
+/// Splits array items at commas which are not quoted.
+fn split_config_array(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for ch in value.chars() {
+        match (quote, ch) {
+            _ if escaped => escaped = false,
+            (Some('"'), '\\') => escaped = true,
+            (Some(q), ch) if ch == q => quote = None,
+            (None, '"') | (None, '\'') => quote = Some(ch),
+            (None, ',') => {
+                items.push(item.clone());
+                item.clear();
+                continue;
+            },
+            _ => {},
+        }
+        item.push(ch);
+    }
+    if !item.trim().is_empty() {
+        items.push(item);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::args;
+
+    #[test]
+    fn parses_config() {
+        let config = Config::parse([
+            "# comment",
+            "region = eu-west # trailing",
+            "",
+            "[remote.add]",
+            "branch = \"main\"",
+            "tags = [\"a,b\", 'c', d]",
+            "; comment",
+            "[database]",
+            "url = 'postgres://x'",
+        ].join("\n")).unwrap();
+        assert_eq!(config.values("", "region"), Some(&vec!["eu-west".to_string()]));
+        assert_eq!(config.values("remote.add", "branch"), Some(&vec!["main".to_string()]));
+        assert_eq!(config.values("remote.add", "tags"), Some(&vec!["a,b".to_string(), "c".to_string(), "d".to_string()]));
+        assert_eq!(config.values("database", "url"), Some(&vec!["postgres://x".to_string()]));
+        assert_eq!(config.values("remote", "branch"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert_eq!(
+            Config::parse("a = 1\nb").unwrap_err().kind(),
+            &ErrorKind::InvalidConfig("line 2".to_string()),
+        );
+        assert!(Config::parse("a = \"unterminated").is_err());
+    }
+
+    #[test]
+    fn extracts_config_path() {
+        let app = Command::<crate::Context>::with_name("app").with_config_name("app");
+        assert_eq!(
+            extract_config_path(&app, args(&["a", "--config", "x.toml", "b", "--", "--config", "y"])).unwrap(),
+            (args(&["a", "b", "--", "--config", "y"]), Some("x.toml".to_string())),
+        );
+        assert!(extract_config_path(&app, args(&["--config"])).is_err());
+        let app = Command::<crate::Context>::with_name("app");
+        assert_eq!(extract_config_path(&app, args(&["--config", "x"])).unwrap(), (args(&["--config", "x"]), None));
+        let app = Command::<crate::Context>::with_name("app").with_config_name("app")
+            .with_subcommand(Command::with_name("b").with_flag(Flag::with_name("config").accept_value()));
+        assert_eq!(extract_config_path(&app, args(&["b", "--config", "x"])).unwrap(), (args(&["b", "--config", "x"]), None));
+        let app = Command::<crate::Context>::with_name("app").with_config_name("app")
+            .with_config_flag(Flag::with_name("config").with_alias("c").accept_value())
+            .with_flag(Flag::with_name("count").with_alias("c"));
+        assert_eq!(active_config_flag(&app).unwrap().alias(), &None);
+        assert_eq!(extract_config_path(&app, args(&["-c", "--config", "x"])).unwrap(), (args(&["-c"]), Some("x".to_string())));
+    }
+
+    #[test]
+    fn merges_config() {
+        let config = Config::parse("a = 1\n[x]\nb = 2").unwrap()
+            .merge(Config::parse("a = 3\nc = 4\n[x]\nb = 5").unwrap());
+        assert_eq!(config.values("", "a"), Some(&vec!["1".to_string()]));
+        assert_eq!(config.values("", "c"), Some(&vec!["4".to_string()]));
+        assert_eq!(config.values("x", "b"), Some(&vec!["2".to_string()]));
+    }
+
+    #[test]
+    fn loads_config_in_order_of_precedence() {
+        let name = format!("rawcmd-precedence-{}", std::process::id());
+        let dir = std::env::temp_dir().join(&name);
+        let local = PathBuf::from(format!(".{}.toml", name));
+        fs::create_dir_all(dir.join(&name)).unwrap();
+        fs::write(dir.join(&name).join("config.toml"), "a = xdg\nb = xdg\nc = xdg").unwrap();
+        fs::write(&local, "a = local\nb = local").unwrap();
+        fs::write(dir.join("explicit.toml"), "a = explicit").unwrap();
+        let mut vars = HashMap::new();
+        vars.insert("XDG_CONFIG_HOME".to_string(), dir.to_str().unwrap().to_string());
+        let explicit = Some(dir.join("explicit.toml").to_str().unwrap().to_string());
+        let config = load_config(&name, explicit, &vars);
+        fs::remove_file(&local).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let config = config.unwrap();
+        assert_eq!(config.values("", "a"), Some(&vec!["explicit".to_string()]));
+        assert_eq!(config.values("", "b"), Some(&vec!["local".to_string()]));
+        assert_eq!(config.values("", "c"), Some(&vec!["xdg".to_string()]));
+    }
+
+    #[test]
+    fn builds_config_values_of_global_flags() {
+        let output = Flag::with_name("output").accept_value().global();
+        let token = Flag::with_name("token").accept_value().global();
+        let app = Command::<crate::Context>::with_name("app")
+            .with_flag(output.clone())
+            .with_flag(Flag::with_name("region").accept_value())
+            .with_subcommand(
+                Command::with_name("remote")
+                    .with_flag(output.clone())
+                    .with_flag(token.clone())
+                    .with_subcommand(Command::with_name("add").with_flag(output).with_flag(token))
+            );
+        let config = Config::parse("output = a\nregion = b\n[remote]\ntoken = c\n[remote.add]\noutput = d").unwrap();
+        let remote = &app.commands()[0];
+        let values = build_config_values(&config, &[&app, remote, &remote.commands()[0]]);
+        assert_eq!(values.get("output"), Some(&vec!["d".to_string()]));
+        assert_eq!(values.get("token"), Some(&vec!["c".to_string()]));
+        assert_eq!(values.get("region"), None);
+        let values = build_config_values(&config, &[&app, remote]);
+        assert_eq!(values.get("output"), Some(&vec!["a".to_string()]));
+    }
+}
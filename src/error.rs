@@ -4,9 +4,11 @@ use crate::ErrorKind;
 
 #[derive(Debug)]
 pub struct Error {
-    kind: ErrorKind,
+    kind: Box<ErrorKind>,
     message: String,
     status: i32,
+    suggestions: Vec<String>,
+    choices: Vec<String>,
     source: Option<Box<dyn error::Error + 'static>>,
 }
 
@@ -16,7 +18,9 @@ impl Error {
         Self {
             message: error_message(&kind),
             status: error_status(&kind),
-            kind,
+            kind: Box::new(kind),
+            suggestions: Vec::new(),
+            choices: Vec::new(),
             source: None,
         }
     }
@@ -28,11 +32,25 @@ impl Error {
         Self {
             message: error_message(&kind),
             status: error_status(&kind),
-            kind,
+            kind: Box::new(kind),
+            suggestions: Vec::new(),
+            choices: Vec::new(),
             source: Some(Box::new(source)),
         }
     }
 
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self.message = build_message(&self.kind, &self.suggestions, &self.choices);
+        self
+    }
+
+    pub fn with_choices(mut self, choices: Vec<String>) -> Self {
+        self.choices = choices;
+        self.message = build_message(&self.kind, &self.suggestions, &self.choices);
+        self
+    }
+
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
@@ -45,6 +63,14 @@ impl Error {
         &self.status
     }
 
+    pub fn suggestions(&self) -> &Vec<String> {
+        &self.suggestions
+    }
+
+    pub fn choices(&self) -> &Vec<String> {
+        &self.choices
+    }
+
     pub fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         self.source.as_ref().map(|e| e.as_ref())
     }
@@ -55,7 +81,9 @@ impl std::default::Default for Error {
         Self {
             message: error_message(&ErrorKind::GeneralError),
             status: error_status(&ErrorKind::GeneralError),
-            kind: ErrorKind::GeneralError,
+            kind: Box::new(ErrorKind::GeneralError),
+            suggestions: Vec::new(),
+            choices: Vec::new(),
             source: None,
         }
     }
@@ -80,16 +108,58 @@ impl fmt::Display for Error {
 }
 
 fn error_message(kind: &ErrorKind) -> String {
+    describe_error(kind, &|name| name.to_string(), &|number| number.to_string())
+}
+
+/// Returns the message of an error kind where names and numbers of the kind
+/// are rendered by `text` and `number`.
+fn describe_error(kind: &ErrorKind, text: &dyn Fn(&str) -> String, number: &dyn Fn(usize) -> String) -> String {
     match kind {
         ErrorKind::GeneralError => format!("Unknown error occurred while processing."),
-        ErrorKind::UnknownCommand(name) => format!("The requested command `{}` does not exist.", name),
-        ErrorKind::MissingCommandResolver(name) => format!("The requested command `{}` does not have a resolver.", name),
-        ErrorKind::CommandFailed(name) => format!("The requested command `{}` failed to execute.", name),
-        ErrorKind::UnknownFlag(name) => format!("The provided flag `{}` does not exist.", name),
-        ErrorKind::MissingFlagValue(name) => format!("The provided flag `{}` should have a value.", name),
-        ErrorKind::InvalidFlagValue(name) => format!("The provided flag `{}` has invalid value.", name),
-        ErrorKind::InvalidParamValue(index) => format!("The provided param `{}` has invalid value.", index),
-        ErrorKind::ToManyParams(expected, found) => format!("Too many parameters where provided (received {} but accepts only {}).", expected, found),
+        ErrorKind::UnknownCommand(name) => format!("The requested command `{}` does not exist.", text(name)),
+        ErrorKind::MissingCommandResolver(name) => format!("The requested command `{}` does not have a resolver.", text(name)),
+        ErrorKind::CommandFailed(name) => format!("The requested command `{}` failed to execute.", text(name)),
+        ErrorKind::UnknownFlag(name) => format!("The provided flag `{}` does not exist.", text(name)),
+        ErrorKind::MissingFlagValue(name) => format!("The provided flag `{}` should have a value.", text(name)),
+        ErrorKind::InvalidFlagValue(name) => format!("The provided flag `{}` has invalid value.", text(name)),
+        ErrorKind::InvalidParamValue(index) => format!("The provided param `{}` has invalid value.", number(*index)),
+        ErrorKind::ToManyParams(expected, found) => format!("Too many parameters where provided (received {} but accepts only {}).", number(*expected), number(*found)),
+        ErrorKind::MissingRequiredFlag(name) => format!("The required flag `{}` was not provided.", text(name)),
+        ErrorKind::MissingRequiredParam(name) => format!("The required param `{}` was not provided.", text(name)),
+        ErrorKind::DuplicateFlag(name) => format!("The provided flag `{}` can not be used more than once.", text(name)),
+        ErrorKind::TooFewFlagOccurrences(name, min, found) => format!("The flag `{}` should be provided at least {} times (received {}).", text(name), number(*min), number(*found)),
+        ErrorKind::TooManyFlagOccurrences(name, max, found) => format!("The flag `{}` can be provided at most {} times (received {}).", text(name), number(*max), number(*found)),
+        ErrorKind::InvalidConfig(source) => format!("The configuration `{}` could not be loaded.", text(source)),
+        ErrorKind::TooFewParamValues(name, min, found) => format!("The param `{}` should have at least {} values (received {}).", text(name), number(*min), number(*found)),
+        ErrorKind::TooManyParamValues(name, max, found) => format!("The param `{}` can have at most {} values (received {}).", text(name), number(*max), number(*found)),
+        ErrorKind::MissingTail(name) => format!("The command `{}` requires arguments after `--`.", text(name)),
+        ErrorKind::UnexpectedTail(name) => format!("The command `{}` does not accept arguments after `--`.", text(name)),
+        ErrorKind::AmbiguousCommand(name) => format!("The requested command `{}` is ambiguous.", text(name)),
+        ErrorKind::AmbiguousFlag(name) => format!("The provided flag `{}` is ambiguous.", text(name)),
+        ErrorKind::ConflictingGlobalFlag(name) => format!("The global flag `{}` is declared again by a subcommand.", text(name)),
+        ErrorKind::MultipleVariadicParams(name) => format!("The param `{}` can not be variadic because the command already has a variadic param.", text(name)),
+    }
+}
+
+/// Returns the message of an error kind followed by the suggested values and
+/// the allowed values.
+fn build_message(kind: &ErrorKind, suggestions: &[String], choices: &[String]) -> String {
+    let mut message = error_message(kind);
+    if let Some(items) = quote_items(suggestions) {
+        message = format!("{} Did you mean {}?", message, items);
+    }
+    if let Some(items) = quote_items(choices) {
+        message = format!("{} Possible values are {}.", message, items);
+    }
+    message
+}
+
+fn quote_items(items: &[String]) -> Option<String> {
+    let mut items: Vec<String> = items.iter().map(|s| format!("`{}`", s)).collect();
+    let last = items.pop()?;
+    match items.is_empty() {
+        true => Some(last),
+        false => Some(format!("{} or {}", items.join(", "), last)),
     }
 }
 
@@ -104,5 +174,84 @@ fn error_status(kind: &ErrorKind) -> i32 {
         ErrorKind::InvalidFlagValue(_) => 70,
         ErrorKind::InvalidParamValue(_) => 71,
         ErrorKind::ToManyParams(_, _) => 72,
+        ErrorKind::MissingRequiredFlag(_) => 73,
+        ErrorKind::MissingRequiredParam(_) => 74,
+        ErrorKind::DuplicateFlag(_) => 75,
+        ErrorKind::TooFewFlagOccurrences(_, _, _) => 76,
+        ErrorKind::TooManyFlagOccurrences(_, _, _) => 77,
+        ErrorKind::InvalidConfig(_) => 78,
+        ErrorKind::TooFewParamValues(_, _, _) => 79,
+        ErrorKind::TooManyParamValues(_, _, _) => 80,
+        ErrorKind::MissingTail(_) => 81,
+        ErrorKind::UnexpectedTail(_) => 82,
+        ErrorKind::AmbiguousCommand(_) => 83,
+        ErrorKind::AmbiguousFlag(_) => 84,
+        ErrorKind::ConflictingGlobalFlag(_) => 85,
+        ErrorKind::MultipleVariadicParams(_) => 86,
+    }
+}
+
+/// Returns exit statuses of all error kinds with their messages where names
+/// and numbers are replaced by placeholders.
+pub(crate) fn error_statuses() -> Vec<(i32, String)> {
+    let empty = String::new;
+    let kinds = vec![
+        ErrorKind::GeneralError,
+        ErrorKind::UnknownCommand(empty()),
+        ErrorKind::MissingCommandResolver(empty()),
+        ErrorKind::CommandFailed(empty()),
+        ErrorKind::UnknownFlag(empty()),
+        ErrorKind::MissingFlagValue(empty()),
+        ErrorKind::InvalidFlagValue(empty()),
+        ErrorKind::InvalidParamValue(0),
+        ErrorKind::ToManyParams(0, 0),
+        ErrorKind::MissingRequiredFlag(empty()),
+        ErrorKind::MissingRequiredParam(empty()),
+        ErrorKind::DuplicateFlag(empty()),
+        ErrorKind::TooFewFlagOccurrences(empty(), 0, 0),
+        ErrorKind::TooManyFlagOccurrences(empty(), 0, 0),
+        ErrorKind::InvalidConfig(empty()),
+        ErrorKind::TooFewParamValues(empty(), 0, 0),
+        ErrorKind::TooManyParamValues(empty(), 0, 0),
+        ErrorKind::MissingTail(empty()),
+        ErrorKind::UnexpectedTail(empty()),
+        ErrorKind::AmbiguousCommand(empty()),
+        ErrorKind::AmbiguousFlag(empty()),
+        ErrorKind::ConflictingGlobalFlag(empty()),
+        ErrorKind::MultipleVariadicParams(empty()),
+    ];
+    kinds.iter().map(|kind| {
+        (error_status(kind), describe_error(kind, &|_| "NAME".to_string(), &|_| "N".to_string()))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_suggestions() {
+        let error = Error::new(ErrorKind::UnknownCommand("stat".to_string()));
+        assert_eq!(error.message(), "The requested command `stat` does not exist.");
+        let error = error.with_suggestions(vec!["status".to_string()]);
+        assert_eq!(error.message(), "The requested command `stat` does not exist. Did you mean `status`?");
+        let error = error.with_suggestions(vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        assert_eq!(error.message(), "The requested command `stat` does not exist. Did you mean `a`, `b` or `c`?");
+        assert_eq!(error.suggestions(), &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn appends_choices() {
+        let error = Error::new(ErrorKind::InvalidFlagValue("format".to_string()))
+            .with_suggestions(vec!["json".to_string()])
+            .with_choices(vec!["json".to_string(), "yaml".to_string()]);
+        assert_eq!(error.message(), "The provided flag `format` has invalid value. Did you mean `json`? Possible values are `json` or `yaml`.");
+        let error = error.with_suggestions(vec!["yaml".to_string()]);
+        assert_eq!(error.message(), "The provided flag `format` has invalid value. Did you mean `yaml`? Possible values are `json` or `yaml`.");
+        let error = Error::new(ErrorKind::InvalidFlagValue("format".to_string()))
+            .with_choices(vec!["json".to_string()])
+            .with_suggestions(vec!["json".to_string()]);
+        assert_eq!(error.message(), "The provided flag `format` has invalid value. Did you mean `json`? Possible values are `json`.");
+        assert_eq!(error.choices(), &["json"]);
     }
 }
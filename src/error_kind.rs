@@ -1,4 +1,5 @@
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ErrorKind {
     GeneralError,
     UnknownCommand(String),
@@ -6,7 +7,21 @@ pub enum ErrorKind {
     CommandFailed(String), // prepared
     UnknownFlag(String),
     MissingFlagValue(String),
-    InvalidFlagValue(String), // prepared
-    InvalidParamValue(usize,), // prepared
+    InvalidFlagValue(String),
+    InvalidParamValue(usize,),
     ToManyParams(usize, usize),
+    MissingRequiredFlag(String),
+    MissingRequiredParam(String),
+    DuplicateFlag(String),
+    TooFewFlagOccurrences(String, usize, usize),
+    TooManyFlagOccurrences(String, usize, usize),
+    InvalidConfig(String),
+    TooFewParamValues(String, usize, usize),
+    TooManyParamValues(String, usize, usize),
+    MissingTail(String),
+    UnexpectedTail(String),
+    AmbiguousCommand(String),
+    AmbiguousFlag(String),
+    ConflictingGlobalFlag(String),
+    MultipleVariadicParams(String),
 }
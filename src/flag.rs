@@ -5,7 +5,16 @@ pub struct Flag {
     alias: Option<String>,
     description: Option<String>,
     default_value: Option<String>,
+    env: Option<String>,
+    required: bool,
     accepts_value: bool,
+    multiple: bool,
+    count: bool,
+    global: bool,
+    negatable: bool,
+    choices: Vec<String>,
+    min_occurrences: Option<usize>,
+    max_occurrences: Option<usize>,
 }
 
 /// Flag structure implementation.
@@ -31,10 +40,56 @@ impl Flag {
         &self.default_value
     }
 
+    /// Returns name of the environment variable which provides the value.
+    pub fn env(&self) -> &Option<String> {
+        &self.env
+    }
+
+    /// Returns true if the flag must be provided.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
     /// Returns true if the flag accepts value.
     pub fn accepts_value(&self) -> bool {
         self.accepts_value
     }
+
+    /// Returns true if the flag can be provided multiple times.
+    pub fn is_multiple(&self) -> bool {
+        self.multiple
+    }
+
+    /// Returns true if the flag counts its occurrences (e.g. `-vvv`).
+    pub fn is_count(&self) -> bool {
+        self.count
+    }
+
+    /// Returns true if the flag is accepted by all subcommands.
+    pub fn is_global(&self) -> bool {
+        self.global
+    }
+
+    /// Returns true if the flag can be switched off with `--no-<name>` which
+    /// only applies to flags that do not accept a value.
+    pub fn is_negatable(&self) -> bool {
+        self.negatable && !self.accepts_value
+    }
+
+    /// Returns values which are allowed.
+    pub fn choices(&self) -> &Vec<String> {
+        &self.choices
+    }
+
+    /// Returns minimum number of occurrences of a multiple flag.
+    pub fn min_occurrences(&self) -> Option<usize> {
+        self.min_occurrences
+    }
+
+    /// Returns maximum number of occurrences of a multiple flag.
+    pub fn max_occurrences(&self) -> Option<usize> {
+        self.max_occurrences
+    }
 }
 
 /// Flag structure implementation.
@@ -47,7 +102,16 @@ impl Flag {
             alias: None,
             description: None,
             default_value: None,
+            env: None,
+            required: false,
             accepts_value: false,
+            multiple: false,
+            count: false,
+            global: false,
+            negatable: false,
+            choices: Vec::new(),
+            min_occurrences: None,
+            max_occurrences: None,
         }
     }
 
@@ -57,6 +121,12 @@ impl Flag {
         self
     }
 
+    /// Removes alias name.
+    pub(crate) fn without_alias(mut self) -> Self {
+        self.alias = None;
+        self
+    }
+
     /// Sets description.
     pub fn with_description<S: Into<String>>(mut self, val: S) -> Self {
         self.description = Some(val.into());
@@ -69,9 +139,71 @@ impl Flag {
         self
     }
 
+    /// Sets environment variable which provides the value when the flag is
+    /// not provided in arguments.
+    pub fn with_env<S: Into<String>>(mut self, val: S) -> Self {
+        self.env = Some(val.into());
+        self
+    }
+
     /// Sets value.
     pub fn accept_value(mut self) -> Self {
         self.accepts_value = true;
         self
     }
+
+    /// Allows the flag to be provided multiple times and collects its values.
+    pub fn multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    /// Allows the flag to be repeated and records how many times it was
+    /// provided (e.g. `-vvv` or `--verbose --verbose`).
+    pub fn count(mut self) -> Self {
+        self.count = true;
+        self
+    }
+
+    /// Makes the flag available to all descendants of the command which
+    /// declares it, anywhere in the arguments.
+    pub fn global(mut self) -> Self {
+        self.global = true;
+        self
+    }
+
+    /// Makes a switch accept `--no-<name>` which explicitly turns it off. It
+    /// has no effect on flags which accept a value.
+    pub fn negatable(mut self) -> Self {
+        self.negatable = true;
+        self
+    }
+
+    /// Restricts values to a fixed set (e.g. `["json", "yaml", "table"]`).
+    pub fn with_choices<I, S>(mut self, vals: I) -> Self
+        where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.choices = vals.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets minimum number of occurrences of a multiple flag.
+    pub fn with_min_occurrences(mut self, val: usize) -> Self {
+        self.min_occurrences = Some(val);
+        self
+    }
+
+    /// Sets maximum number of occurrences of a multiple flag.
+    pub fn with_max_occurrences(mut self, val: usize) -> Self {
+        self.max_occurrences = Some(val);
+        self
+    }
+
+    /// Marks the flag as mandatory.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
 }
@@ -2,14 +2,18 @@ use std::str::FromStr;
 
 /// Structure which holds flag summary.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FlagSummary {
     name: String,
     alias: Option<String>,
     description: Option<String>,
     value: Option<String>,
+    values: Vec<String>,
     default_value: Option<String>,
     accepts_value: bool,
     provided: bool,
+    count: usize,
+    counted: bool,
 }
 
 /// Structure implementation.
@@ -31,12 +35,34 @@ impl FlagSummary {
             name: name.into(),
             alias,
             description,
+            values: value.iter().cloned().collect(),
             value,
             default_value,
             accepts_value,
+            count: provided as usize,
             provided,
+            counted: false,
         }
     }
+
+    /// Sets the number of times the flag was provided.
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Marks the flag as a counter of occurrences (e.g. `-vvv`).
+    pub fn counted(mut self) -> Self {
+        self.counted = true;
+        self
+    }
+
+    /// Sets all values of a flag which was provided multiple times.
+    pub fn with_values(mut self, values: Vec<String>) -> Self {
+        self.value = values.first().cloned();
+        self.values = values;
+        self
+    }
 }
 
 /// Structure implementation.
@@ -76,6 +102,31 @@ impl FlagSummary {
         }
     }
 
+    /// Returns all values of a flag.
+    pub fn values(&self) -> &Vec<String> {
+        &self.values
+    }
+
+    /// Returns all values converted to a specific type or `None` if any of
+    /// them can not be converted.
+    pub fn to_values<T>(&self) -> Option<Vec<T>>
+        where
+        T: FromStr,
+    {
+        self.values.iter().map(|v| v.parse::<T>().ok()).collect()
+    }
+
+    /// Returns the explicit state of a switch where the last value takes
+    /// precedence (e.g. `false` for `--no-color`) and a provided flag without
+    /// values is `true`. Returns `None` when the state is unknown.
+    pub fn to_bool(&self) -> Option<bool> {
+        match self.values.last() {
+            Some(value) => value.parse::<bool>().ok(),
+            None if self.provided => Some(true),
+            None => None,
+        }
+    }
+
     /// Returns default value.
     pub fn default_value(&self) -> &Option<String> {
         &self.default_value
@@ -90,4 +141,40 @@ impl FlagSummary {
     pub fn provided(&self) -> bool {
         self.provided
     }
+
+    /// Returns the number of times the flag was provided.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns true if the flag counts its occurrences.
+    pub fn is_count(&self) -> bool {
+        self.counted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_values() {
+        let summary = FlagSummary::with_name("a", None, None, None, None, true, true)
+            .with_values(vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(summary.value(), &Some("1".to_string()));
+        assert_eq!(summary.to_values::<usize>(), Some(vec![1, 2]));
+        let summary = summary.with_values(vec!["1".to_string(), "x".to_string()]);
+        assert_eq!(summary.to_values::<usize>(), None);
+    }
+
+    #[test]
+    fn converts_to_bool() {
+        let summary = |provided, values: &[&str]| FlagSummary::with_name("a", None, None, None, None, false, provided)
+            .with_values(values.iter().map(|v| v.to_string()).collect());
+        assert_eq!(summary(false, &[]).to_bool(), None);
+        assert_eq!(summary(true, &[]).to_bool(), Some(true));
+        assert_eq!(summary(true, &["true", "false"]).to_bool(), Some(false));
+        assert_eq!(summary(false, &["true"]).to_bool(), Some(true));
+        assert_eq!(summary(false, &["x"]).to_bool(), None);
+    }
 }
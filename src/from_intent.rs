@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use crate::{Intent, Result};
+
+/// Conversion of parsed command-line data into a typed value.
+pub trait FromIntent: Sized {
+
+    /// Returns the value built from the intent.
+    fn from_intent(intent: &Intent) -> Result<Self>;
+}
+
+/// Collects flag and param values by name where params take precedence over
+/// flags with the same name.
+impl FromIntent for HashMap<String, String> {
+    fn from_intent(intent: &Intent) -> Result<Self> {
+        let flags = intent.flags().iter().filter_map(|f| f.value().clone().map(|v| (f.name().to_string(), v)));
+        let params = intent.params().iter().filter_map(|p| p.value().clone().map(|v| (p.name().to_string(), v)));
+        Ok(flags.chain(params).collect())
+    }
+}
+
+macro_rules! tuple_from_intent {
+    ($($name:ident),+) => {
+        impl<$($name: FromIntent),+> FromIntent for ($($name,)+) {
+            fn from_intent(intent: &Intent) -> Result<Self> {
+                Ok(($($name::from_intent(intent)?,)+))
+            }
+        }
+    };
+}
+
+tuple_from_intent!(A);
+tuple_from_intent!(A, B);
+tuple_from_intent!(A, B, C);
+tuple_from_intent!(A, B, C, D);
+tuple_from_intent!(A, B, C, D, E);
+tuple_from_intent!(A, B, C, D, E, F);
+tuple_from_intent!(A, B, C, D, E, F, G);
+tuple_from_intent!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandSummary, FlagSummary, ParamSummary};
+
+    #[test]
+    fn extracts_maps_and_tuples() {
+        let intent = Intent::new(
+            vec![],
+            CommandSummary::with_name("", None, None, None, None),
+            vec![],
+            vec![],
+            vec![
+                FlagSummary::with_name("a", None, None, Some("1".to_string()), None, true, true),
+                FlagSummary::with_name("b", None, None, None, None, false, false),
+            ],
+            vec![ParamSummary::with_name("c", None, Some("2".to_string()), None, true)],
+            vec![],
+        );
+        let map = intent.extract::<HashMap<String, String>>().unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"], "1");
+        assert_eq!(map["c"], "2");
+        let (first, second) = intent.extract::<(HashMap<String, String>, HashMap<String, String>)>().unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,411 @@
+use crate::{Result, Command, Flag, Param, Tail, build_subcommand_positions, find_subcommand, global_flag_width, match_flag_arg, active_version_flag,
+    active_config_flag};
+
+/// Returns formatted help text for a command.
+pub fn render_help<C>(command: &Command<C>) -> String {
+    render_help_with_path(command, &[])
+}
+
+/// Returns formatted help text for a command where `path` holds the names of
+/// parent commands in a tree.
+pub(crate) fn render_help_with_path<C>(command: &Command<C>, path: &[String]) -> String {
+    let mut sections = Vec::new();
+
+    if let Some(about) = command.about() {
+        sections.push(about.to_string());
+    }
+
+    sections.push(render_section("USAGE", vec![render_usage(command, path)]));
+
+    if let Some(description) = command.description() {
+        sections.push(render_section("DESCRIPTION", description.lines().map(String::from).collect()));
+    }
+
+    let commands: Vec<(String, Option<String>)> = command.commands().iter()
+        .map(|c| (c.name().to_string(), render_command_description(c)))
+        .collect();
+    if !commands.is_empty() {
+        sections.push(render_section("COMMANDS", render_rows(commands)));
+    }
+
+    let (globals, flags): (Vec<Flag>, Vec<Flag>) = build_visible_flags(command).into_iter()
+        .partition(|f| f.is_global());
+    let flags: Vec<(String, Option<String>)> = flags.iter()
+        .map(|f| (render_flag_name(f), render_flag_description(f)))
+        .collect();
+    if !flags.is_empty() {
+        sections.push(render_section("FLAGS", render_rows(flags)));
+    }
+
+    let globals: Vec<(String, Option<String>)> = globals.iter()
+        .map(|f| (render_flag_name(f), render_flag_description(f)))
+        .collect();
+    if !globals.is_empty() {
+        sections.push(render_section("GLOBAL FLAGS", render_rows(globals)));
+    }
+
+    let params: Vec<(String, Option<String>)> = command.params().iter()
+        .map(|p| (render_param_name(p), render_param_description(p)))
+        .collect();
+    if !params.is_empty() {
+        sections.push(render_section("PARAMS", render_rows(params)));
+    }
+
+    sections.join("\n\n")
+}
+
+/// Returns the usage line of a command.
+pub(crate) fn render_usage<C>(command: &Command<C>, path: &[String]) -> String {
+    let mut items: Vec<String> = path.to_vec();
+    items.push(command.name().to_string());
+    if !command.commands().is_empty() {
+        items.push("[COMMAND]".to_string());
+    }
+    if !build_visible_flags(command).is_empty() {
+        items.push("[FLAGS]".to_string());
+    }
+    for param in command.params().iter() {
+        items.push(render_param_name(param));
+    }
+    match command.tail() {
+        Some(Tail::Optional) => items.push("[-- <TAIL>...]".to_string()),
+        Some(Tail::Required) => items.push("-- <TAIL>...".to_string()),
+        _ => (),
+    }
+    items.join(" ")
+}
+
+/// Returns the name column of a flag (e.g. `-f, --flag <VALUE>...`,
+/// `--flag <a|b>` or `--[no-]flag`).
+pub(crate) fn render_flag_name(flag: &Flag) -> String {
+    let long = match flag.is_negatable() {
+        true => format!("--[no-]{}", flag.name()),
+        false => format!("--{}", flag.name()),
+    };
+    let mut name = match flag.alias() {
+        Some(alias) => format!("-{}, {}", alias, long),
+        None => format!("    {}", long),
+    };
+    if flag.accepts_value() && !flag.choices().is_empty() {
+        name.push_str(&format!(" <{}>", flag.choices().join("|")));
+    } else if flag.accepts_value() {
+        name.push_str(" <VALUE>");
+    }
+    if flag.is_multiple() || flag.is_count() {
+        name.push_str("...");
+    }
+    name
+}
+
+/// Returns the description column of a subcommand with its aliases.
+pub(crate) fn render_command_description<C>(command: &Command<C>) -> Option<String> {
+    let mut items = Vec::new();
+    if let Some(about) = command.about() {
+        items.push(about.to_string());
+    }
+    if !command.aliases().is_empty() {
+        items.push(format!("[aliases: {}]", command.aliases().join(", ")));
+    }
+    join_nonempty(items)
+}
+
+/// Returns the description column of a flag.
+pub(crate) fn render_flag_description(flag: &Flag) -> Option<String> {
+    let mut items = Vec::new();
+    if let Some(description) = flag.description() {
+        items.push(description.to_string());
+    }
+    if let Some(env) = flag.env() {
+        items.push(format!("[env: {}]", env));
+    }
+    if let Some(default_value) = flag.default_value() {
+        items.push(format!("[default: {}]", default_value));
+    }
+    if flag.is_required() {
+        items.push("[required]".to_string());
+    }
+    join_nonempty(items)
+}
+
+/// Returns the name column of a param (e.g. `[param]`, `[param...]` or
+/// `<param>` when required).
+pub(crate) fn render_param_name(param: &Param) -> String {
+    let name = match param.is_variadic() {
+        true => format!("{}...", param.name()),
+        false => param.name().to_string(),
+    };
+    match param.is_required() {
+        true => format!("<{}>", name),
+        false => format!("[{}]", name),
+    }
+}
+
+/// Returns the description column of a param.
+pub(crate) fn render_param_description(param: &Param) -> Option<String> {
+    let mut items = Vec::new();
+    if let Some(description) = param.description() {
+        items.push(description.to_string());
+    }
+    if !param.choices().is_empty() {
+        items.push(format!("[choices: {}]", param.choices().join("|")));
+    }
+    if let Some(default_value) = param.default_value() {
+        items.push(format!("[default: {}]", default_value));
+    }
+    join_nonempty(items)
+}
+
+/// Returns command flags including the automatic help and version flags.
+pub(crate) fn build_visible_flags<C>(command: &Command<C>) -> Vec<Flag> {
+    let mut flags = command.flags().clone();
+    if has_help_flag(command) {
+        flags.push(
+            Flag::with_name("help")
+                .with_alias("h")
+                .with_description("Prints help information.")
+        );
+    }
+    if let Some(flag) = active_version_flag(command) {
+        flags.push(flag);
+    }
+    if let Some(flag) = active_config_flag(command) {
+        flags.push(flag);
+    }
+    flags
+}
+
+/// Returns global flags of a command which are inherited by its subcommands
+/// including the config path flag.
+pub(crate) fn build_global_flags<C>(command: &Command<C>) -> Vec<Flag> {
+    let mut flags: Vec<Flag> = command.flags().iter().filter(|f| f.is_global()).cloned().collect();
+    flags.extend(active_config_flag(command));
+    flags
+}
+
+/// Returns true if the automatic `--help` flag does not clash with the
+/// command's own flags.
+pub(crate) fn has_help_flag<C>(command: &Command<C>) -> bool {
+    !command.flags().iter().any(|f| {
+        f.name() == "help" || f.alias().as_deref() == Some("h")
+    })
+}
+
+/// Returns positions of the command for which help was requested through the
+/// `--help` and `-h` flags or through the `help <COMMAND>` form.
+pub(crate) fn build_help_positions<C>(app: &Command<C>, args: &[String]) -> Result<Option<Vec<usize>>> {
+    let mut positions = Vec::new();
+    let mut command = app;
+    let mut stage = 0; // 0..command, 1..flag or param
+    let mut skip = 0;
+    for (index, arg) in args.iter().enumerate() {
+        if skip > 0 {
+            skip -= 1;
+        } else if arg == "--" {
+            break;
+        } else if has_help_flag(command) && (arg == "--help" || arg == "-h") {
+            return Ok(Some(positions));
+        } else if arg.starts_with('-') {
+            if stage != 0 || global_flag_width(command, arg).is_none() {
+                stage = 1;
+            }
+            if let Ok(matches) = match_flag_arg(command, arg) {
+                if let Some((flag, None)) = matches.last() {
+                    if flag.accepts_value() {
+                        skip = 1;
+                    }
+                }
+            }
+        } else if stage == 0 {
+            if let Some(position) = find_subcommand(command, arg)? {
+                positions.push(position);
+                command = &command.commands()[position];
+            } else if arg == "help" && !command.commands().is_empty() {
+                let rest = build_subcommand_positions(command, &args[index + 1..])?;
+                positions.extend(rest);
+                return Ok(Some(positions));
+            } else {
+                stage = 1;
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns a titled section with indented lines.
+fn render_section(title: &str, lines: Vec<String>) -> String {
+    let mut items = vec![format!("{}:", title)];
+    for line in lines.iter() {
+        items.push(format!("    {}", line).trim_end().to_string());
+    }
+    items.join("\n")
+}
+
+/// Returns aligned two-column rows.
+fn render_rows(rows: Vec<(String, Option<String>)>) -> Vec<String> {
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    rows.iter().map(|(name, description)| match description {
+        Some(description) => format!("{:width$}    {}", name, description, width = width),
+        None => name.to_string(),
+    }).collect()
+}
+
+/// Returns space-separated items or `None` when empty.
+fn join_nonempty(items: Vec<String>) -> Option<String> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(items.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::args;
+    use crate::Context;
+
+    #[test]
+    fn renders_help() {
+        let command = Command::<Context>::with_name("app")
+            .with_about("Application")
+            .with_description("Long description.")
+            .with_flag(Flag::with_name("aaa").with_alias("a").with_description("Flag a").accept_value().with_default_value("x"))
+            .with_flag(Flag::with_name("bbb"))
+            .with_param(Param::with_name("ccc").with_description("Param c"))
+            .with_subcommand(Command::with_name("ddd").with_about("Command d"));
+        assert_eq!(render_help(&command), [
+            "Application",
+            "",
+            "USAGE:",
+            "    app [COMMAND] [FLAGS] [ccc]",
+            "",
+            "DESCRIPTION:",
+            "    Long description.",
+            "",
+            "COMMANDS:",
+            "    ddd    Command d",
+            "",
+            "FLAGS:",
+            "    -a, --aaa <VALUE>    Flag a [default: x]",
+            "        --bbb",
+            "    -h, --help           Prints help information.",
+            "",
+            "PARAMS:",
+            "    [ccc]    Param c",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn renders_global_flags() {
+        let command = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("aaa"))
+            .with_flag(Flag::with_name("bbb").with_description("Flag b").global());
+        assert!(render_help(&command).ends_with([
+            "FLAGS:",
+            "        --aaa",
+            "    -h, --help    Prints help information.",
+            "",
+            "GLOBAL FLAGS:",
+            "        --bbb    Flag b",
+        ].join("\n").as_str()));
+    }
+
+    #[test]
+    fn renders_negatable_flags() {
+        let command = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("color").with_alias("c").negatable());
+        assert!(render_help(&command).contains("    -c, --[no-]color\n"));
+        let command = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("color").with_alias("c").accept_value().negatable());
+        assert!(render_help(&command).contains("    -c, --color <VALUE>\n"));
+    }
+
+    #[test]
+    fn renders_choices() {
+        let command = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("format").accept_value().with_choices(vec!["json", "yaml", "table"]))
+            .with_param(Param::with_name("mode").with_choices(vec!["fast", "slow"]));
+        let help = render_help(&command);
+        assert!(help.contains("    --format <json|yaml|table>\n"));
+        assert!(help.contains("    [mode]    [choices: fast|slow]"));
+    }
+
+    #[test]
+    fn renders_command_aliases() {
+        let command = Command::<Context>::with_name("app")
+            .with_subcommand(Command::with_name("remove").with_about("Removes files").with_alias("rm").with_alias("del"));
+        assert!(render_help(&command).contains("    remove    Removes files [aliases: rm, del]"));
+    }
+
+    #[test]
+    fn renders_required_items() {
+        let command = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("aaa").required())
+            .with_param(Param::with_name("bbb").required());
+        let help = render_help(&command);
+        assert!(help.contains("    app [FLAGS] <bbb>\n"));
+        assert!(help.contains("    --aaa     [required]\n"));
+        assert!(help.ends_with("PARAMS:\n    <bbb>"));
+    }
+
+    #[test]
+    fn renders_flag_env() {
+        let command = Command::<Context>::with_name("app")
+            .with_flag(Flag::with_name("token").accept_value().with_env("APP_TOKEN"));
+        assert!(render_help(&command).contains("    --token <VALUE>    [env: APP_TOKEN]\n"));
+    }
+
+    #[test]
+    fn renders_version_flag() {
+        let command = Command::<Context>::with_name("app").with_version("1.0.0");
+        assert!(render_help(&command).contains("    -V, --version    Prints version information."));
+    }
+
+    #[test]
+    fn renders_usage_with_path() {
+        let command = Command::<Context>::with_name("ccc")
+            .with_param(Param::with_name("ddd"));
+        assert_eq!(render_usage(&command, &["aaa".to_string(), "bbb".to_string()]), "aaa bbb ccc [FLAGS] [ddd]");
+        let command = Command::<Context>::with_name("ccc").with_tail(Tail::Required);
+        assert_eq!(render_usage(&command, &[]), "ccc [FLAGS] -- <TAIL>...");
+    }
+
+    #[test]
+    fn builds_help_positions() {
+        let command = Command::<Context>::with_name("000")
+            .with_subcommand(
+                Command::with_name("aaa")
+                    .with_subcommand(Command::with_name("bbb"))
+                    .with_subcommand(Command::with_name("ccc"))
+            );
+        assert_eq!(build_help_positions(&command, &args(&["aaa"])).unwrap(), None);
+        assert_eq!(build_help_positions(&command, &args(&["--help"])).unwrap(), Some(vec![]));
+        assert_eq!(build_help_positions(&command, &args(&["aaa", "ccc", "-h"])).unwrap(), Some(vec![0, 1]));
+        assert_eq!(build_help_positions(&command, &args(&["help", "aaa", "bbb"])).unwrap(), Some(vec![0, 0]));
+        assert_eq!(build_help_positions(&command, &args(&["aaa", "help", "ccc"])).unwrap(), Some(vec![0, 1]));
+        assert_eq!(build_help_positions(&command, &args(&["aaa", "--", "--help"])).unwrap(), None);
+        assert!(build_help_positions(&command, &args(&["help", "xxx"])).is_err());
+    }
+
+    #[test]
+    fn skips_flag_values_in_help_positions() {
+        let command = Command::<Context>::with_name("000")
+            .with_flag(Flag::with_name("message").with_alias("m").accept_value())
+            .with_flag(Flag::with_name("output").accept_value().global())
+            .with_subcommand(Command::with_name("aaa"));
+        assert_eq!(build_help_positions(&command, &args(&["--message", "-h"])).unwrap(), None);
+        assert_eq!(build_help_positions(&command, &args(&["-m", "--help"])).unwrap(), None);
+        assert_eq!(build_help_positions(&command, &args(&["--output", "-h", "aaa"])).unwrap(), None);
+        assert_eq!(build_help_positions(&command, &args(&["--message", "x", "-h"])).unwrap(), Some(vec![]));
+        assert_eq!(build_help_positions(&command, &args(&["--output", "x", "aaa", "-h"])).unwrap(), Some(vec![0]));
+    }
+
+    #[test]
+    fn respects_custom_help_flag() {
+        let command = Command::<Context>::with_name("000")
+            .with_flag(Flag::with_name("help"));
+        let args = vec!["--help".to_string()];
+        assert_eq!(build_help_positions(&command, &args).unwrap(), None);
+        assert!(!render_help(&command).contains("Prints help information."));
+    }
+}
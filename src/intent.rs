@@ -1,7 +1,10 @@
-use crate::{CommandSummary, FlagSummary, ParamSummary, ResourceSummary};
+use std::str::FromStr;
+use crate::{Result, Error, ErrorKind, CommandSummary, FlagSummary, ParamSummary,
+    ResourceSummary, FromIntent};
 
 /// Intent structure which represents user intent.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Intent {
     args: Vec<String>,
     command: CommandSummary,
@@ -10,6 +13,7 @@ pub struct Intent {
     flags: Vec<FlagSummary>,
     params: Vec<ParamSummary>,
     resources: Vec<ResourceSummary>,
+    tail: Vec<String>,
 }
 
 /// Intent structure implementation.
@@ -32,10 +36,17 @@ impl Intent {
             subcommands,
             flags,
             params,
-            resources
+            resources,
+            tail: Vec::new(),
         }
     }
 
+    /// Sets arguments which follow the `--` separator.
+    pub fn with_tail(mut self, tail: Vec<String>) -> Self {
+        self.tail = tail;
+        self
+    }
+
     /// Returns raw command-line arguments.
     pub fn args(&self) -> &Vec<String> {
         &self.args
@@ -67,6 +78,42 @@ impl Intent {
         self.flags.iter().find(|f| *f.name() == name)
     }
 
+    /// Returns the number of times a flag was provided.
+    pub fn flag_count<S: Into<String>>(&self, name: S) -> usize {
+        self.flag(name).map_or(0, |f| f.count())
+    }
+
+    /// Returns the value of a flag converted to a specific type where a value
+    /// which can not be converted raises `ErrorKind::InvalidFlagValue`.
+    pub fn flag_value<T, S>(&self, name: S) -> Result<Option<T>>
+        where
+        T: FromStr,
+        S: Into<String>,
+    {
+        let name = name.into();
+        match self.flag(name.as_str()).and_then(|f| f.value().as_ref()) {
+            Some(value) => value.parse::<T>()
+                .map(Some)
+                .map_err(|_| Error::new(ErrorKind::InvalidFlagValue(name))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns all values of a flag converted to a specific type.
+    pub fn flag_values<T, S>(&self, name: S) -> Result<Vec<T>>
+        where
+        T: FromStr,
+        S: Into<String>,
+    {
+        let name = name.into();
+        match self.flag(name.as_str()) {
+            Some(flag) => flag.values().iter()
+                .map(|v| v.parse::<T>().map_err(|_| Error::new(ErrorKind::InvalidFlagValue(name.clone()))))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Returns summary objects of all params.
     pub fn params(&self) -> &Vec<ParamSummary> {
         &self.params
@@ -78,6 +125,51 @@ impl Intent {
         self.params.iter().find(|f| *f.name() == name)
     }
     
+    /// Returns the value of a param converted to a specific type where a
+    /// value which can not be converted raises `ErrorKind::InvalidParamValue`
+    /// with the position of the param.
+    pub fn param_value<T, S>(&self, name: S) -> Result<Option<T>>
+        where
+        T: FromStr,
+        S: Into<String>,
+    {
+        let name = name.into();
+        let index = self.params.iter().position(|p| *p.name() == name);
+        match index.and_then(|i| self.params[i].value().as_ref().map(|v| (i, v))) {
+            Some((index, value)) => value.parse::<T>()
+                .map(Some)
+                .map_err(|_| Error::new(ErrorKind::InvalidParamValue(index))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns all values of a param converted to a specific type.
+    pub fn param_values<T, S>(&self, name: S) -> Result<Vec<T>>
+        where
+        T: FromStr,
+        S: Into<String>,
+    {
+        let name = name.into();
+        match self.params.iter().position(|p| *p.name() == name) {
+            Some(index) => self.params[index].values().iter()
+                .map(|v| v.parse::<T>().map_err(|_| Error::new(ErrorKind::InvalidParamValue(index))))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Converts the intent into a typed value.
+    pub fn extract<T: FromIntent>(&self) -> Result<T> {
+        T::from_intent(self)
+    }
+
+    /// Deserializes flags, params, the `tail` and the command `path` into a
+    /// type where dashes in names become underscores.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+        T::deserialize(self)
+    }
+
     /// Returns summary objects of all resources.
     pub fn resources(&self) -> &Vec<ResourceSummary> {
         &self.resources
@@ -89,6 +181,11 @@ impl Intent {
         self.resources.iter().find(|f| *f.name() == name)
     }
 
+    /// Returns arguments which follow the `--` separator.
+    pub fn tail(&self) -> &Vec<String> {
+        &self.tail
+    }
+
     /// Returns true if command-line arguments are present.
     pub fn has_args(&self) -> bool {
         !self.args.is_empty()
@@ -100,6 +197,11 @@ impl Intent {
         self.args.iter().any(|a| *a == name)
     }
 
+    /// Returns true if arguments follow the `--` separator.
+    pub fn has_tail(&self) -> bool {
+        !self.tail.is_empty()
+    }
+
     /// Returns true if the executed command has parent commands.
     pub fn has_supcommands(&self) -> bool {
         !self.supcommands.is_empty()
@@ -204,6 +306,34 @@ mod tests {
         assert_eq!(intent.flag("b").unwrap().name(), "b");
     }
 
+    #[test]
+    fn provides_flag_count() {
+        let intent = intent_with_flags(vec![
+            FlagSummary::with_name("a", None, None, None, None, false, true).with_count(3),
+        ]);
+        assert_eq!(intent.flag_count("a"), 3);
+        assert_eq!(intent.flag_count("b"), 0);
+    }
+
+    #[test]
+    fn converts_values() {
+        let intent = intent_with_flags(vec![
+            FlagSummary::with_name("a", None, None, Some("1".to_string()), None, true, true),
+            FlagSummary::with_name("b", None, None, Some("x".to_string()), None, true, true),
+        ]);
+        assert_eq!(intent.flag_value::<i32, _>("a"), Ok(Some(1)));
+        assert_eq!(intent.flag_value::<i32, _>("c"), Ok(None));
+        assert_eq!(intent.flag_values::<i32, _>("a"), Ok(vec![1]));
+        assert_eq!(intent.flag_value::<i32, _>("b").unwrap_err().kind(), &ErrorKind::InvalidFlagValue("b".to_string()));
+        let intent = intent_with_params(vec![
+            ParamSummary::with_name("a", None, None, None, false),
+            ParamSummary::with_name("b", None, Some("x".to_string()), None, true),
+        ]);
+        assert_eq!(intent.param_value::<String, _>("b"), Ok(Some("x".to_string())));
+        assert_eq!(intent.param_value::<i32, _>("b").unwrap_err().kind(), &ErrorKind::InvalidParamValue(1));
+        assert_eq!(intent.param_values::<i32, _>("b").unwrap_err().kind(), &ErrorKind::InvalidParamValue(1));
+    }
+
     #[test]
     fn provides_param_by_name() {
         let intent = intent_with_params(vec![
@@ -222,6 +352,14 @@ mod tests {
         assert_eq!(intent.has_arg("b"), false);
     }
 
+    #[test]
+    fn provides_tail() {
+        let intent = intent_with_args(vec![]).with_tail(vec!["a".to_string()]);
+        assert_eq!(intent.tail(), &["a"]);
+        assert!(intent.has_tail());
+        assert!(!intent_with_args(vec![]).has_tail());
+    }
+
     #[test]
     fn checks_flag_existance() {
         let intent = intent_with_flags(vec![
@@ -0,0 +1,358 @@
+use std::fmt;
+use std::io;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::forward_to_deserialize_any;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use crate::{Error, ErrorKind, Intent};
+
+/// Origin of a deserialized value which decides the reported error.
+enum Origin {
+    Flag(String),
+    Param(usize),
+    Other(String),
+}
+
+/// Deserializer of a single flag, param, tail or command path value where
+/// strings are coerced into the requested type and a counting switch is
+/// read as a number.
+struct ValueDeserializer {
+    values: Vec<String>,
+    switch: Option<bool>,
+    count: usize,
+    counted: bool,
+    present: bool,
+}
+
+/// Map of intent entries where names use underscores instead of dashes.
+struct IntentMapAccess {
+    entries: std::vec::IntoIter<(String, Origin, ValueDeserializer)>,
+    current: Option<(Origin, ValueDeserializer)>,
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::with_source(io::Error::other(msg.to_string()), ErrorKind::GeneralError)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::new(ErrorKind::InvalidFlagValue(field.replace('_', "-")))
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 5)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("status", self.status())?;
+        state.serialize_field("suggestions", self.suggestions())?;
+        state.serialize_field("choices", self.choices())?;
+        state.end()
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &Intent {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut entries = Vec::new();
+        for flag in self.flags().iter() {
+            let switch = match flag.accepts_value() {
+                true => None,
+                false => Some(flag.to_bool().unwrap_or(false)),
+            };
+            entries.push((flag.name().replace('-', "_"), Origin::Flag(flag.name().to_string()), ValueDeserializer {
+                values: flag.values().clone(),
+                switch,
+                count: flag.count(),
+                counted: flag.is_count(),
+                present: flag.provided() || !flag.values().is_empty(),
+            }));
+        }
+        for (index, param) in self.params().iter().enumerate() {
+            entries.push((param.name().replace('-', "_"), Origin::Param(index), ValueDeserializer::list(param.values().clone())));
+        }
+        let path: Vec<String> = self.supcommands().iter()
+            .chain(std::iter::once(self.command()))
+            .map(|c| c.name().to_string())
+            .collect();
+        for (name, values) in vec![("path", path), ("tail", self.tail().clone())].into_iter() {
+            if entries.iter().all(|(key, _, _)| key != name) {
+                entries.push((name.to_string(), Origin::Other(name.to_string()), ValueDeserializer::list(values)));
+            }
+        }
+        visitor.visit_map(IntentMapAccess {
+            entries: entries.into_iter(),
+            current: None,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> MapAccess<'de> for IntentMapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some((key, origin, value)) => {
+                self.current = Some((origin, value));
+                seed.deserialize(key.into_deserializer()).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (origin, value) = self.current.take().expect("value requested before key");
+        seed.deserialize(value).map_err(|_| match origin {
+            Origin::Flag(name) => Error::new(ErrorKind::InvalidFlagValue(name)),
+            Origin::Param(index) => Error::new(ErrorKind::InvalidParamValue(index)),
+            Origin::Other(name) => Error::new(ErrorKind::InvalidFlagValue(name)),
+        })
+    }
+}
+
+impl ValueDeserializer {
+
+    /// Returns a deserializer of a list of values.
+    fn list(values: Vec<String>) -> Self {
+        Self {
+            present: !values.is_empty(),
+            values,
+            switch: None,
+            count: 0,
+            counted: false,
+        }
+    }
+
+    /// Returns the first value or an error when there is none.
+    fn first(&self) -> Result<&String, Error> {
+        self.values.first().ok_or_else(|| de::Error::custom("missing value"))
+    }
+
+    /// Returns the first value parsed into a specific type.
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, Error> {
+        self.first()?.parse::<T>().map_err(|_| de::Error::custom("invalid value"))
+    }
+}
+
+macro_rules! deserialize_number {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.switch {
+                Some(_) => visitor.$visit(self.count as $ty),
+                None => visitor.$visit(self.parse::<$ty>()?),
+            }
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match (self.switch, self.values.len()) {
+            (Some(_), _) if self.counted => visitor.visit_u64(self.count as u64),
+            (Some(switch), _) => visitor.visit_bool(switch),
+            (None, 0) => visitor.visit_unit(),
+            (None, 1) => visitor.visit_string(self.values[0].clone()),
+            (None, _) => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.switch {
+            Some(switch) => visitor.visit_bool(switch),
+            None => visitor.visit_bool(self.parse::<bool>()?),
+        }
+    }
+
+    deserialize_number!(deserialize_i8, visit_i8, i8);
+    deserialize_number!(deserialize_i16, visit_i16, i16);
+    deserialize_number!(deserialize_i32, visit_i32, i32);
+    deserialize_number!(deserialize_i64, visit_i64, i64);
+    deserialize_number!(deserialize_u8, visit_u8, u8);
+    deserialize_number!(deserialize_u16, visit_u16, u16);
+    deserialize_number!(deserialize_u32, visit_u32, u32);
+    deserialize_number!(deserialize_u64, visit_u64, u64);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.parse::<f32>()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.parse::<f64>()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_char(self.parse::<char>()?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.first()?.clone())
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.first()?.clone())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.present {
+            true => visitor.visit_some(self),
+            false => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let items = self.values.into_iter().map(|v| ValueDeserializer::list(vec![v]));
+        visitor.visit_seq(de::value::SeqDeserializer::new(items))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.first()?.clone().into_deserializer())
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use crate::{CommandSummary, FlagSummary, ParamSummary};
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Format { Json, Yaml }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Args {
+        verbose: u8,
+        force: bool,
+        dry_run: Option<bool>,
+        depth: u32,
+        format: Format,
+        include: Vec<String>,
+        files: Vec<String>,
+        path: Vec<String>,
+        tail: Vec<String>,
+    }
+
+    fn build_intent(depth: &str) -> Intent {
+        Intent::new(
+            vec![],
+            CommandSummary::with_name("b", None, None, None, None),
+            vec![CommandSummary::with_name("a", None, None, None, None)],
+            vec![],
+            vec![
+                FlagSummary::with_name("verbose", None, None, None, None, false, true).with_count(2).counted(),
+                FlagSummary::with_name("force", None, None, None, None, false, false),
+                FlagSummary::with_name("dry-run", None, None, None, None, false, false),
+                FlagSummary::with_name("depth", None, None, Some(depth.to_string()), None, true, true),
+                FlagSummary::with_name("format", None, None, Some("yaml".to_string()), None, true, true),
+                FlagSummary::with_name("include", None, None, None, None, true, true)
+                    .with_values(vec!["x".to_string(), "y".to_string()]),
+            ],
+            vec![
+                ParamSummary::with_name("files", None, None, None, true)
+                    .with_values(vec!["1".to_string(), "2".to_string()]),
+            ],
+            vec![],
+        ).with_tail(vec!["--z".to_string()])
+    }
+
+    #[test]
+    fn deserializes_intent() {
+        assert_eq!(build_intent("3").deserialize::<Args>().unwrap(), Args {
+            verbose: 2,
+            force: false,
+            dry_run: None,
+            depth: 3,
+            format: Format::Yaml,
+            include: vec!["x".to_string(), "y".to_string()],
+            files: vec!["1".to_string(), "2".to_string()],
+            path: vec!["a".to_string(), "b".to_string()],
+            tail: vec!["--z".to_string()],
+        });
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let err = build_intent("x").deserialize::<Args>().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidFlagValue("depth".to_string()));
+        #[derive(Debug, Deserialize)]
+        struct Files { #[allow(dead_code)] files: Vec<bool> }
+        let err = build_intent("3").deserialize::<Files>().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidParamValue(0));
+        #[derive(Debug, Deserialize)]
+        struct Output { #[allow(dead_code)] output: String }
+        let err = build_intent("3").deserialize::<Output>().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidFlagValue("output".to_string()));
+        let err = build_intent("3").deserialize::<u32>().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::GeneralError);
+        assert!(err.source().unwrap().to_string().starts_with("invalid type"));
+    }
+
+    #[test]
+    fn deserializes_switches_by_kind() {
+        #[derive(Debug, Deserialize)]
+        struct Switches { verbose: serde_json::Value, force: serde_json::Value }
+        let switches = build_intent("3").deserialize::<Switches>().unwrap();
+        assert_eq!(switches.verbose, serde_json::json!(2));
+        assert_eq!(switches.force, serde_json::json!(false));
+        let intent = Intent::new(
+            vec![],
+            CommandSummary::with_name("b", None, None, None, None),
+            vec![],
+            vec![],
+            vec![
+                FlagSummary::with_name("verbose", None, None, None, None, false, true).counted(),
+                FlagSummary::with_name("force", None, None, None, None, false, true).with_count(2),
+            ],
+            vec![],
+            vec![],
+        );
+        let switches = intent.deserialize::<Switches>().unwrap();
+        assert_eq!(switches.verbose, serde_json::json!(1));
+        assert_eq!(switches.force, serde_json::json!(true));
+    }
+
+    #[test]
+    fn serializes_intent_and_error() {
+        let json = serde_json::to_value(build_intent("3")).unwrap();
+        assert_eq!(json["command"]["name"], "b");
+        assert_eq!(json["flags"][0]["count"], 2);
+        assert_eq!(json["tail"][0], "--z");
+        let json = serde_json::to_value(Error::new(ErrorKind::UnknownFlag("-x".to_string()))).unwrap();
+        assert_eq!(json["kind"]["UnknownFlag"], "-x");
+        assert_eq!(json["status"], 68);
+    }
+}
@@ -1,11 +1,32 @@
+mod after_hook;
+#[cfg(feature = "async")]
+mod async_after_hook;
+#[cfg(feature = "async")]
+mod async_before_hook;
+#[cfg(feature = "async")]
+mod async_command_handler;
+#[cfg(feature = "async")]
+mod async_command_resolver;
+mod before_hook;
+#[cfg(feature = "async")]
+mod box_future;
 mod command_summary;
+mod completions;
+mod config;
 mod command;
 mod context;
 mod error;
 mod error_kind;
 mod flag_summary;
 mod flag;
+mod from_intent;
+mod help;
 mod intent;
+#[cfg(feature = "serde")]
+mod intent_deserializer;
+mod man;
+mod manpage;
+mod markdown;
 mod param_summary;
 mod param;
 mod command_handler;
@@ -13,16 +34,40 @@ mod command_resolver;
 mod resource_summary;
 mod resource;
 mod result;
+mod shell;
+mod tail;
+#[cfg(test)]
+mod test_utils;
 mod utils;
+mod version;
 
+pub use after_hook::*;
+#[cfg(feature = "async")]
+pub use async_after_hook::*;
+#[cfg(feature = "async")]
+pub use async_before_hook::*;
+#[cfg(feature = "async")]
+pub use async_command_handler::*;
+#[cfg(feature = "async")]
+pub use async_command_resolver::*;
+pub use before_hook::*;
+#[cfg(feature = "async")]
+pub use box_future::*;
 pub use command_summary::*;
+pub use completions::*;
+pub use config::*;
 pub use command::*;
 pub use context::*;
 pub use error::*;
 pub use error_kind::*;
 pub use flag_summary::*;
 pub use flag::*;
+pub use from_intent::*;
+pub use help::*;
 pub use intent::*;
+pub use man::*;
+pub use manpage::*;
+pub use markdown::*;
 pub use param_summary::*;
 pub use param::*;
 pub use command_handler::*;
@@ -30,4 +75,9 @@ pub use command_resolver::*;
 pub use resource_summary::*;
 pub use resource::*;
 pub use result::*;
+pub use shell::*;
+pub use tail::*;
 use utils::*;
+pub use version::*;
+#[cfg(feature = "derive")]
+pub use rawcmd_derive::Command;
@@ -0,0 +1,306 @@
+use crate::{Command, Flag, Param, Tail, Manpage, build_visible_flags, build_global_flags, error_statuses, render_command_description};
+
+/// Returns man pages of a command and all its subcommands.
+pub fn render_manpages<C>(app: &Command<C>) -> Vec<Manpage> {
+    let mut pages = Vec::new();
+    build_manpages(app, app, &[], &[], &mut pages);
+    pages
+}
+
+/// Collects man pages of a command and its descendants where `path` holds
+/// the names of parent commands and `globals` their global flags.
+fn build_manpages<C>(app: &Command<C>, command: &Command<C>, path: &[String], globals: &[Flag], pages: &mut Vec<Manpage>) {
+    pages.push(render_manpage(app, command, path, globals));
+
+    let mut path = path.to_vec();
+    path.push(command.name().to_string());
+    let mut globals = globals.to_vec();
+    globals.extend(build_global_flags(command));
+    for subcommand in command.commands().iter() {
+        build_manpages(app, subcommand, &path, &globals, pages);
+    }
+}
+
+/// Returns man page of a single command.
+fn render_manpage<C>(app: &Command<C>, command: &Command<C>, path: &[String], globals: &[Flag]) -> Manpage {
+    let mut names = path.to_vec();
+    names.push(command.name().to_string());
+    let name = names.join("-");
+
+    let version = command.version().as_ref().or_else(|| app.version().as_ref());
+    let footer = match version {
+        Some(version) => format!("{} {}", app.name(), version),
+        None => app.name().to_string(),
+    };
+    let mut lines = vec![
+        format!(".TH {} 1 \"\" \"{}\"", escape(&name.to_uppercase()), escape(&footer)),
+        ".SH NAME".to_string(),
+        match command.about() {
+            Some(about) => format!("{} \\- {}", escape(&name), escape(about)),
+            None => escape(&name),
+        },
+        ".SH SYNOPSIS".to_string(),
+        render_synopsis(command, &names),
+    ];
+
+    if let Some(description) = command.description() {
+        lines.push(".SH DESCRIPTION".to_string());
+        lines.push(escape_text(description));
+    }
+
+    let mut flags = build_visible_flags(command);
+    flags.extend(globals.iter().cloned());
+    for (title, global) in [("OPTIONS", false), ("GLOBAL OPTIONS", true)].iter() {
+        let items: Vec<&Flag> = flags.iter().filter(|f| f.is_global() == *global).collect();
+        if items.is_empty() {
+            continue;
+        }
+        lines.push(format!(".SH {}", title));
+        for flag in items.iter() {
+            lines.push(".TP".to_string());
+            lines.push(render_flag(flag));
+            if let Some(text) = render_flag_text(flag) {
+                lines.push(text);
+            }
+        }
+    }
+
+    if !command.params().is_empty() {
+        lines.push(".SH ARGUMENTS".to_string());
+        for param in command.params().iter() {
+            lines.push(".TP".to_string());
+            lines.push(format!("\\fI{}\\fR", escape(param.name())));
+            if let Some(text) = render_param_text(param) {
+                lines.push(text);
+            }
+        }
+    }
+
+    if !command.commands().is_empty() {
+        lines.push(".SH COMMANDS".to_string());
+        for subcommand in command.commands().iter() {
+            lines.push(".TP".to_string());
+            lines.push(format!("\\fB{}\\fR(1)", escape(&format!("{}-{}", name, subcommand.name()))));
+            if let Some(text) = render_command_description(subcommand) {
+                lines.push(escape_text(&text));
+            }
+        }
+    }
+
+    let env_flags: Vec<&Flag> = flags.iter().filter(|f| f.env().is_some()).collect();
+    if !command.resources().is_empty() || !env_flags.is_empty() {
+        lines.push(".SH ENVIRONMENT".to_string());
+        for flag in env_flags.iter() {
+            lines.push(".TP".to_string());
+            lines.push(format!("\\fB{}\\fR", escape(flag.env().as_ref().unwrap())));
+            lines.push(format!("Provides the value of \\fB{}\\fR.", escape(&format!("--{}", flag.name()))));
+        }
+        for resource in command.resources().iter() {
+            lines.push(".TP".to_string());
+            lines.push(format!("\\fB{}\\fR", escape(resource.name())));
+            if let Some(description) = resource.description() {
+                lines.push(escape_text(description));
+            }
+        }
+    }
+
+    lines.push(".SH EXIT STATUS".to_string());
+    lines.push(".TP".to_string());
+    lines.push("\\fB0\\fR".to_string());
+    lines.push("Successful execution.".to_string());
+    for (status, description) in error_statuses().iter() {
+        lines.push(".TP".to_string());
+        lines.push(format!("\\fB{}\\fR", status));
+        lines.push(escape_text(description));
+    }
+
+    if let Some(author) = command.author().as_ref().or_else(|| app.author().as_ref()) {
+        lines.push(".SH AUTHOR".to_string());
+        lines.push(escape_text(author));
+    }
+
+    let mut links = Vec::new();
+    if !path.is_empty() {
+        links.push(path.join("-"));
+    }
+    for subcommand in command.commands().iter() {
+        links.push(format!("{}-{}", name, subcommand.name()));
+    }
+    if !links.is_empty() {
+        lines.push(".SH SEE ALSO".to_string());
+        lines.push(links.iter()
+            .map(|l| format!("\\fB{}\\fR(1)", escape(l)))
+            .collect::<Vec<String>>()
+            .join(", "));
+    }
+
+    Manpage::with_name(name, lines.join("\n") + "\n")
+}
+
+/// Returns the synopsis line of a command.
+fn render_synopsis<C>(command: &Command<C>, names: &[String]) -> String {
+    let mut items = vec![format!("\\fB{}\\fR", escape(&names.join(" ")))];
+    for flag in build_visible_flags(command).iter() {
+        match flag.is_required() {
+            true => items.push(render_flag(flag).replace(", ", "|")),
+            false => items.push(format!("[{}]", render_flag(flag).replace(", ", "|"))),
+        }
+    }
+    for param in command.params().iter() {
+        let name = match param.is_variadic() {
+            true => format!("\\fI{}\\fR...", escape(param.name())),
+            false => format!("\\fI{}\\fR", escape(param.name())),
+        };
+        match param.is_required() {
+            true => items.push(name),
+            false => items.push(format!("[{}]", name)),
+        }
+    }
+    if !command.commands().is_empty() {
+        items.push("[\\fICOMMAND\\fR]".to_string());
+    }
+    match command.tail() {
+        Some(Tail::Optional) => items.push("[\\-\\- \\fITAIL\\fR...]".to_string()),
+        Some(Tail::Required) => items.push("\\-\\- \\fITAIL\\fR...".to_string()),
+        _ => (),
+    }
+    items.join(" ")
+}
+
+/// Returns the formatted flag name (e.g. `-f, --flag VALUE`).
+fn render_flag(flag: &Flag) -> String {
+    let mut items = Vec::new();
+    if let Some(alias) = flag.alias() {
+        items.push(format!("\\fB{}\\fR", escape(&format!("-{}", alias))));
+    }
+    match flag.is_negatable() {
+        true => items.push(format!("\\fB{}\\fR", escape(&format!("--[no-]{}", flag.name())))),
+        false => items.push(format!("\\fB{}\\fR", escape(&format!("--{}", flag.name())))),
+    }
+    let mut text = items.join(", ");
+    if flag.accepts_value() && !flag.choices().is_empty() {
+        text.push_str(&format!(" \\fI{}\\fR", escape(&flag.choices().join("|"))));
+    } else if flag.accepts_value() {
+        text.push_str(" \\fIVALUE\\fR");
+    }
+    if flag.is_multiple() || flag.is_count() {
+        text.push_str("...");
+    }
+    text
+}
+
+/// Returns the paragraph describing a flag.
+fn render_flag_text(flag: &Flag) -> Option<String> {
+    let mut items = Vec::new();
+    if let Some(description) = flag.description() {
+        items.push(description.to_string());
+    }
+    if let Some(env) = flag.env() {
+        items.push(format!("[env: {}]", env));
+    }
+    if let Some(default_value) = flag.default_value() {
+        items.push(format!("[default: {}]", default_value));
+    }
+    match items.is_empty() {
+        true => None,
+        false => Some(escape_text(&items.join(" "))),
+    }
+}
+
+/// Returns the paragraph describing a param.
+fn render_param_text(param: &Param) -> Option<String> {
+    let mut items = Vec::new();
+    if let Some(description) = param.description() {
+        items.push(description.to_string());
+    }
+    if !param.choices().is_empty() {
+        items.push(format!("[choices: {}]", param.choices().join("|")));
+    }
+    if let Some(default_value) = param.default_value() {
+        items.push(format!("[default: {}]", default_value));
+    }
+    match items.is_empty() {
+        true => None,
+        false => Some(escape_text(&items.join(" "))),
+    }
+}
+
+/// Escapes roff special characters.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// Escapes a block of text and protects lines which would be read as roff
+/// requests.
+fn escape_text(text: &str) -> String {
+    text.lines().map(|line| {
+        let line = escape(line);
+        if line.starts_with('.') || line.starts_with('\'') {
+            format!("\\&{}", line)
+        } else {
+            line
+        }
+    }).collect::<Vec<String>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Resource};
+
+    #[test]
+    fn renders_manpages() {
+        let app = Command::<Context>::with_name("app")
+            .with_about("Application")
+            .with_version("1.0.0")
+            .with_author("John")
+            .with_description("Long description.\n.dotted line")
+            .with_flag(Flag::with_name("output").with_alias("o").with_description("Output file").accept_value().with_default_value("out.txt"))
+            .with_param(Param::with_name("target"))
+            .with_resource(Resource::with_name("APP_HOME").with_description("Home folder"))
+            .with_subcommand(
+                Command::with_name("remote")
+                    .with_about("Manages remotes")
+                    .with_flag(Flag::with_name("token").accept_value().with_env("APP_TOKEN"))
+                    .with_subcommand(Command::with_name("add"))
+            );
+        let pages = render_manpages(&app);
+        let names: Vec<String> = pages.iter().map(|p| p.file_name()).collect();
+        assert_eq!(names, ["app.1", "app-remote.1", "app-remote-add.1"]);
+
+        let content = pages[0].content();
+        assert!(content.starts_with(".TH APP 1 \"\" \"app 1.0.0\"\n.SH NAME\napp \\- Application\n"));
+        assert!(content.contains(".SH SYNOPSIS\n\\fBapp\\fR [\\fB\\-o\\fR|\\fB\\-\\-output\\fR \\fIVALUE\\fR] [\\fB\\-h\\fR|\\fB\\-\\-help\\fR] [\\fB\\-V\\fR|\\fB\\-\\-version\\fR] [\\fItarget\\fR] [\\fICOMMAND\\fR]\n"));
+        assert!(content.contains(".SH DESCRIPTION\nLong description.\n\\&.dotted line\n"));
+        assert!(content.contains(".TP\n\\fB\\-o\\fR, \\fB\\-\\-output\\fR \\fIVALUE\\fR\nOutput file [default: out.txt]\n"));
+        assert!(content.contains(".SH COMMANDS\n.TP\n\\fBapp\\-remote\\fR(1)\nManages remotes\n"));
+        assert!(content.contains(".SH ENVIRONMENT\n.TP\n\\fBAPP_HOME\\fR\nHome folder\n"));
+        assert!(content.contains(".SH EXIT STATUS\n.TP\n\\fB0\\fR\nSuccessful execution.\n.TP\n\\fB1\\fR\n"));
+        assert!(content.contains(".SH AUTHOR\nJohn\n"));
+        assert!(content.ends_with(".SH SEE ALSO\n\\fBapp\\-remote\\fR(1)\n"));
+
+        let content = pages[1].content();
+        assert!(content.starts_with(".TH APP\\-REMOTE 1 \"\" \"app 1.0.0\"\n"));
+        assert!(content.contains(".SH SYNOPSIS\n\\fBapp remote\\fR"));
+        assert!(content.contains(".TP\n\\fB\\-\\-token\\fR \\fIVALUE\\fR\n[env: APP_TOKEN]\n"));
+        assert!(content.contains(".SH ENVIRONMENT\n.TP\n\\fBAPP_TOKEN\\fR\nProvides the value of \\fB\\-\\-token\\fR.\n"));
+        assert!(content.ends_with(".SH SEE ALSO\n\\fBapp\\fR(1), \\fBapp\\-remote\\-add\\fR(1)\n"));
+    }
+
+    #[test]
+    fn renders_exit_statuses() {
+        let app = Command::<Context>::with_name("app");
+        let content = render_manpages(&app)[0].content().to_string();
+        let section = content.split(".SH EXIT STATUS\n").nth(1).unwrap();
+        let section = section.split(".SH ").next().unwrap();
+        let statuses: Vec<i32> = section.lines()
+            .filter(|l| l.starts_with("\\fB"))
+            .map(|l| l.trim_start_matches("\\fB").trim_end_matches("\\fR").parse().unwrap())
+            .collect();
+        assert_eq!(statuses[..2], [0, 1]);
+        assert!(statuses[1..].windows(2).all(|w| w[0] < w[1]));
+        assert!(section.contains(".TP\n\\fB65\\fR\nThe requested command `NAME` does not exist.\n"));
+        assert!(section.contains(".TP\n\\fB76\\fR\nThe flag `NAME` should be provided at least N times (received N).\n"));
+        assert!(section.contains(".TP\n\\fB81\\fR\nThe command `NAME` requires arguments after `\\-\\-`.\n"));
+    }
+}
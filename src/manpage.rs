@@ -0,0 +1,42 @@
+/// Structure which holds a rendered man page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manpage {
+    name: String,
+    content: String,
+}
+
+/// Structure implementation.
+impl Manpage {
+
+    // Returns new instance.
+    pub fn with_name<
+        S: Into<String>,
+    >(
+        name: S,
+        content: String,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            content,
+        }
+    }
+}
+
+/// Structure implementation.
+impl Manpage {
+
+    /// Returns page name (e.g. `app-remote`).
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Returns roff source of the page.
+    pub fn content(&self) -> &String {
+        &self.content
+    }
+
+    /// Returns file name of the page (e.g. `app-remote.1`).
+    pub fn file_name(&self) -> String {
+        format!("{}.1", self.name)
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{Command, Flag, build_visible_flags, build_global_flags, render_command_description, render_usage};
+
+/// Returns Markdown reference documentation of a command tree.
+pub fn render_markdown<C>(app: &Command<C>) -> String {
+    let mut sections = Vec::new();
+    build_markdown_sections(app, &[], &[], &mut sections);
+    sections.join("\n\n") + "\n"
+}
+
+/// Collects Markdown sections of a command and its descendants where `path`
+/// holds the names of parent commands and `globals` their global flags.
+fn build_markdown_sections<C>(command: &Command<C>, path: &[String], globals: &[Flag], sections: &mut Vec<String>) {
+    let mut names = path.to_vec();
+    names.push(command.name().to_string());
+
+    sections.push(format!("## {}", names.join(" ")));
+    if let Some(about) = command.about() {
+        sections.push(about.to_string());
+    }
+    if let Some(description) = command.description() {
+        sections.push(description.to_string());
+    }
+    sections.push(format!("```\n{}\n```", render_usage(command, path)));
+
+    if !command.commands().is_empty() {
+        let rows = command.commands().iter().map(|c| vec![
+            format!("`{}`", c.name()),
+            cell(&render_command_description(c)),
+        ]).collect();
+        sections.push(render_table("Commands", &["Name", "Description"], rows));
+    }
+
+    let mut flags = build_visible_flags(command);
+    flags.extend(globals.iter().cloned());
+    for (title, global) in [("Flags", false), ("Global flags", true)].iter() {
+        let items: Vec<&Flag> = flags.iter().filter(|f| f.is_global() == *global).collect();
+        if items.is_empty() {
+            continue;
+        }
+        let rows = items.iter().map(|f| vec![
+            match f.is_negatable() {
+                true => format!("`--[no-]{}`", f.name()),
+                false => format!("`--{}`", f.name()),
+            },
+            match f.alias() {
+                Some(alias) => format!("`-{}`", alias),
+                None => String::new(),
+            },
+            match (f.accepts_value(), f.choices().is_empty()) {
+                (true, true) => "yes".to_string(),
+                (true, false) => f.choices().iter().map(|c| format!("`{}`", c)).collect::<Vec<String>>().join(", "),
+                (false, _) => "no".to_string(),
+            },
+            code_cell(f.env()),
+            code_cell(f.default_value()),
+            cell(f.description()),
+        ]).collect();
+        sections.push(render_table(title, &["Name", "Alias", "Takes value", "Env", "Default", "Description"], rows));
+    }
+
+    if !command.params().is_empty() {
+        let rows = command.params().iter().map(|p| vec![
+            format!("`{}`", p.name()),
+            code_cell(p.default_value()),
+            match p.choices().is_empty() {
+                true => cell(p.description()),
+                false => cell(&Some(format!(
+                    "{} [choices: {}]",
+                    p.description().as_deref().unwrap_or(""),
+                    p.choices().iter().map(|c| format!("`{}`", c)).collect::<Vec<String>>().join(", "),
+                ).trim_start().to_string())),
+            },
+        ]).collect();
+        sections.push(render_table("Params", &["Name", "Default", "Description"], rows));
+    }
+
+    if !command.resources().is_empty() {
+        let rows = command.resources().iter().map(|r| vec![
+            format!("`{}`", r.name()),
+            cell(r.description()),
+        ]).collect();
+        sections.push(render_table("Resources", &["Name", "Description"], rows));
+    }
+
+    let mut globals = globals.to_vec();
+    globals.extend(build_global_flags(command));
+    for subcommand in command.commands().iter() {
+        build_markdown_sections(subcommand, &names, &globals, sections);
+    }
+}
+
+/// Returns a titled Markdown table.
+fn render_table(title: &str, header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut lines = vec![
+        format!("**{}**", title),
+        String::new(),
+        format!("| {} |", header.join(" | ")),
+        format!("|{}", " --- |".repeat(header.len())),
+    ];
+    for row in rows.iter() {
+        let cells: Vec<String> = row.iter().map(|c| match c.is_empty() {
+            true => " ".to_string(),
+            false => format!(" {} ", c),
+        }).collect();
+        lines.push(format!("|{}|", cells.join("|")));
+    }
+    lines.join("\n")
+}
+
+/// Returns a table cell with escaped text.
+fn cell(text: &Option<String>) -> String {
+    match text {
+        Some(text) => text.replace('|', "\\|").lines().collect::<Vec<&str>>().join("<br>"),
+        None => String::new(),
+    }
+}
+
+/// Returns a table cell with a code span.
+fn code_cell(text: &Option<String>) -> String {
+    match text {
+        Some(text) => format!("`{}`", text.replace('|', "\\|")),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Flag, Param, Resource};
+
+    #[test]
+    fn renders_markdown() {
+        let app = Command::<Context>::with_name("app")
+            .with_about("Application")
+            .with_flag(Flag::with_name("output").with_alias("o").with_description("Output | file").accept_value().with_env("APP_OUTPUT").with_default_value("out.txt"))
+            .with_param(Param::with_name("target").with_description("Target"))
+            .with_resource(Resource::with_name("APP_HOME").with_description("Home folder"))
+            .with_subcommand(
+                Command::with_name("remote")
+                    .with_about("Manages remotes")
+                    .with_subcommand(Command::with_name("add"))
+            );
+        assert_eq!(render_markdown(&app), [
+            "## app",
+            "",
+            "Application",
+            "",
+            "```",
+            "app [COMMAND] [FLAGS] [target]",
+            "```",
+            "",
+            "**Commands**",
+            "",
+            "| Name | Description |",
+            "| --- | --- |",
+            "| `remote` | Manages remotes |",
+            "",
+            "**Flags**",
+            "",
+            "| Name | Alias | Takes value | Env | Default | Description |",
+            "| --- | --- | --- | --- | --- | --- |",
+            "| `--output` | `-o` | yes | `APP_OUTPUT` | `out.txt` | Output \\| file |",
+            "| `--help` | `-h` | no | | | Prints help information. |",
+            "",
+            "**Params**",
+            "",
+            "| Name | Default | Description |",
+            "| --- | --- | --- |",
+            "| `target` | | Target |",
+            "",
+            "**Resources**",
+            "",
+            "| Name | Description |",
+            "| --- | --- |",
+            "| `APP_HOME` | Home folder |",
+            "",
+            "## app remote",
+            "",
+            "Manages remotes",
+            "",
+            "```",
+            "app remote [COMMAND] [FLAGS]",
+            "```",
+            "",
+            "**Commands**",
+            "",
+            "| Name | Description |",
+            "| --- | --- |",
+            "| `add` | |",
+            "",
+            "**Flags**",
+            "",
+            "| Name | Alias | Takes value | Env | Default | Description |",
+            "| --- | --- | --- | --- | --- | --- |",
+            "| `--help` | `-h` | no | | | Prints help information. |",
+            "",
+            "## app remote add",
+            "",
+            "```",
+            "app remote add [FLAGS]",
+            "```",
+            "",
+            "**Flags**",
+            "",
+            "| Name | Alias | Takes value | Env | Default | Description |",
+            "| --- | --- | --- | --- | --- | --- |",
+            "| `--help` | `-h` | no | | | Prints help information. |",
+            "",
+        ].join("\n"));
+    }
+}
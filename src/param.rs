@@ -4,6 +4,11 @@ pub struct Param {
     name: String,
     description: Option<String>,
     default_value: Option<String>,
+    required: bool,
+    variadic: bool,
+    choices: Vec<String>,
+    min_values: Option<usize>,
+    max_values: Option<usize>,
 }
 
 /// Param structure implementation.
@@ -23,6 +28,31 @@ impl Param {
     pub fn default_value(&self) -> &Option<String> {
         &self.default_value
     }
+
+    /// Returns true if the param must be provided.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns true if the param collects a list of values.
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+
+    /// Returns values which are allowed.
+    pub fn choices(&self) -> &Vec<String> {
+        &self.choices
+    }
+
+    /// Returns minimum number of values of a variadic param.
+    pub fn min_values(&self) -> Option<usize> {
+        self.min_values
+    }
+
+    /// Returns maximum number of values of a variadic param.
+    pub fn max_values(&self) -> Option<usize> {
+        self.max_values
+    }
 }
 
 /// Param structure implementation.
@@ -34,6 +64,11 @@ impl Param {
             name: name.into(),
             description: None,
             default_value: None,
+            required: false,
+            variadic: false,
+            choices: Vec::new(),
+            min_values: None,
+            max_values: None,
         }
     }
 
@@ -48,4 +83,40 @@ impl Param {
         self.default_value = Some(val.into());
         self
     }
+
+    /// Marks the param as mandatory.
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Makes the param collect a run of values (e.g. `SRC...`). A command
+    /// accepts one variadic param and another one fails with
+    /// `ErrorKind::MultipleVariadicParams`.
+    pub fn variadic(mut self) -> Self {
+        self.variadic = true;
+        self
+    }
+
+    /// Restricts values to a fixed set (e.g. `["json", "yaml", "table"]`).
+    pub fn with_choices<I, S>(mut self, vals: I) -> Self
+        where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.choices = vals.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets minimum number of values of a variadic param.
+    pub fn with_min_values(mut self, val: usize) -> Self {
+        self.min_values = Some(val);
+        self
+    }
+
+    /// Sets maximum number of values of a variadic param.
+    pub fn with_max_values(mut self, val: usize) -> Self {
+        self.max_values = Some(val);
+        self
+    }
 }
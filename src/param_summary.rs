@@ -2,10 +2,12 @@ use std::str::FromStr;
 
 /// Structure which holds param summary.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ParamSummary {
     name: String,
     description: Option<String>,
     value: Option<String>,
+    values: Vec<String>,
     default_value: Option<String>,
     provided: bool,
 }
@@ -26,11 +28,19 @@ impl ParamSummary {
         Self {
             name: name.into(),
             description,
+            values: value.iter().cloned().collect(),
             value,
             default_value,
             provided,
         }
     }
+
+    /// Sets all values of a variadic param.
+    pub fn with_values(mut self, values: Vec<String>) -> Self {
+        self.value = values.first().cloned();
+        self.values = values;
+        self
+    }
 }
 
 /// Structure implementation.
@@ -65,6 +75,20 @@ impl ParamSummary {
         }
     }
 
+    /// Returns all values of a param.
+    pub fn values(&self) -> &Vec<String> {
+        &self.values
+    }
+
+    /// Returns all values converted to a specific type or `None` if any of
+    /// them can not be converted.
+    pub fn to_values<T>(&self) -> Option<Vec<T>>
+        where
+        T: FromStr,
+    {
+        self.values.iter().map(|v| v.parse::<T>().ok()).collect()
+    }
+
     /// Returns default value.
     pub fn default_value(&self) -> &Option<String> {
         &self.default_value
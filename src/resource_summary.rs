@@ -1,5 +1,6 @@
 /// Structure which holds resource summary.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ResourceSummary {
     name: String,
     description: Option<String>,
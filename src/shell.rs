@@ -0,0 +1,7 @@
+/// Shell for which completion scripts can be generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
@@ -0,0 +1,7 @@
+/// Policy for trailing arguments which follow the `--` separator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tail {
+    Optional,
+    Required,
+    Forbidden,
+}
@@ -0,0 +1,16 @@
+use std::collections::HashMap;
+use crate::{Result, Command, FlagSummary, build_flag_summaries};
+
+/// Returns command-line arguments from string slices.
+pub(crate) fn args(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// Returns flag summaries without environment variables and configuration.
+pub(crate) fn flag_summaries<C, A, T>(command: &Command<C>, args: A) -> Result<Vec<FlagSummary>>
+    where
+    A: IntoIterator<Item = T>,
+    T: Into<String>,
+{
+    build_flag_summaries(command, args, &HashMap::new(), &HashMap::new())
+}
@@ -1,6 +1,7 @@
 use std::env;
+use std::collections::HashMap;
 use crate::{Result, Error, ErrorKind, Command, CommandSummary, Flag, FlagSummary,
-    Param, ParamSummary, Resource, ResourceSummary};
+    Param, ParamSummary, Resource, ResourceSummary, Tail};
 
 /// Parses command-line arguments.
 pub fn parse_args() -> Vec<String> {
@@ -8,13 +9,16 @@ pub fn parse_args() -> Vec<String> {
     split_equal_args(&args)
 }
 
-/// Parses command-line arguments.
+/// Parses command-line arguments where arguments after `--` are kept
+/// verbatim.
 pub fn split_equal_args(args: &Vec<String>) -> Vec<String> {
-    let items: Vec<Vec<String>> = args.iter()
+    let index = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    let items: Vec<Vec<String>> = args[..index].iter()
         .map(|a| a.splitn(2, '=').map(|s| s.to_string()).collect())
         .collect();
     let items: Vec<String> = items.iter()
         .flat_map(|tup| tup.iter())
+        .chain(args[index..].iter())
         .cloned()
         .collect();
     items
@@ -37,25 +41,70 @@ pub fn build_subcommand_positions<C, A, T>(app: &Command<C>, args: A) -> Result<
             arg => arg.unwrap(),
         };
         if arg.starts_with("-") {
-            break;
+            match global_flag_width(command, &arg) {
+                Some(width) => {
+                    if width > 1 {
+                        args.pop();
+                    }
+                    continue;
+                },
+                None => break,
+            }
         }
 
-        let commands = &command.commands();
-        let size = &commands.len();
-        for (index, cmd) in commands.iter().enumerate() {
-            command = cmd;
-            if *cmd.name() == arg {
+        let commands = command.commands();
+        if commands.is_empty() {
+            continue;
+        }
+        match find_subcommand(command, &arg)? {
+            Some(index) => {
                 positions.push(index);
-                break;
-            } else if index == size - 1 {
-                return Err(Error::new(ErrorKind::UnknownCommand(arg)));
-            }
+                command = &commands[index];
+            },
+            None => {
+                let candidates = commands.iter()
+                    .flat_map(|c| c.aliases().iter().chain(std::iter::once(c.name())).map(move |n| (n.to_string(), c.name().to_string())))
+                    .collect();
+                let suggestions = build_suggestions(&arg, candidates, command.suggestion_distance());
+                return Err(Error::new(ErrorKind::UnknownCommand(arg)).with_suggestions(suggestions));
+            },
         }
     }
 
     Ok(positions)
 }
 
+/// Returns the position of a subcommand matching `arg` by name or alias,
+/// or by an unambiguous prefix of a name when the command accepts prefixes.
+pub fn find_subcommand<C>(command: &Command<C>, arg: &str) -> Result<Option<usize>> {
+    let commands = command.commands();
+    if let Some(index) = commands.iter().position(|c| c.name() == arg || c.aliases().iter().any(|a| a == arg)) {
+        return Ok(Some(index));
+    } else if !command.accepts_prefixes() || arg.is_empty() {
+        return Ok(None);
+    }
+    let matches: Vec<usize> = (0..commands.len())
+        .filter(|i| commands[*i].name().starts_with(arg))
+        .collect();
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(Some(matches[0])),
+        _ => {
+            let names = matches.iter().map(|i| commands[*i].name().to_string()).collect();
+            Err(Error::new(ErrorKind::AmbiguousCommand(arg.to_string())).with_suggestions(names))
+        },
+    }
+}
+
+/// Returns the name of a global flag when a command or any of its
+/// descendants declares a flag with the same name or alias.
+pub(crate) fn find_conflicting_flag<C>(command: &Command<C>, global: &Flag) -> Option<String> {
+    if command.flags().iter().any(|f| f.name() == global.name() || f.alias().is_some() && f.alias() == global.alias()) {
+        return Some(global.name().to_string());
+    }
+    command.commands().iter().find_map(|c| find_conflicting_flag(c, global))
+}
+
 /// Returns command object based on the position in arguments.
 pub fn subcommand_at_position<'a, C>(app: &'a Command<C>, positions: &Vec<usize>) -> &'a Command<C> {
     let mut command = app;
@@ -77,27 +126,31 @@ pub fn build_command_summary<C>(command: &Command<C>) -> CommandSummary {
 }
 
 /// Returns command summary.
-pub fn build_flag_summary(flag: &Flag, provided: bool, value: &Option<String>) -> FlagSummary {
-    FlagSummary::with_name(
+pub fn build_flag_summary(flag: &Flag, count: usize, values: &[String]) -> FlagSummary {
+    let summary = FlagSummary::with_name(
         flag.name().clone().as_str(),
         flag.alias().clone(),
         flag.description().clone(),
-        value.clone(),
+        None,
         flag.default_value().clone(),
-        flag.accepts_value().clone(),
-        provided.clone(),
-    )
+        flag.accepts_value(),
+        count > 0,
+    ).with_values(values.to_vec()).with_count(count);
+    match flag.is_count() {
+        true => summary.counted(),
+        false => summary,
+    }
 }
 
 /// Returns command summary.
-pub fn build_param_summary(param: &Param, provided: bool, value: &Option<String>) -> ParamSummary {
+pub fn build_param_summary(param: &Param, values: &[String]) -> ParamSummary {
     ParamSummary::with_name(
         param.name().clone().as_str(),
         param.description().clone(),
-        value.clone(),
+        None,
         param.default_value().clone(),
-        provided.clone(),
-    )
+        !values.is_empty(),
+    ).with_values(values.to_vec())
 }
 
 /// Returns resource summary.
@@ -133,14 +186,19 @@ pub fn build_subcommand_summaries<C>(command: &Command<C>) -> Vec<CommandSummary
 }
 
 /// Returns flag summary objects for command. 
-pub fn build_flag_summaries<C, A, T>(command: &Command<C>, args: A) -> Result<Vec<FlagSummary>>
+pub fn build_flag_summaries<C, A, T>(
+    command: &Command<C>,
+    args: A,
+    vars: &HashMap<String, String>,
+    config: &HashMap<String, Vec<String>>,
+) -> Result<Vec<FlagSummary>>
     where
     A: IntoIterator<Item = T>,
     T: Into<String>,
 {
     let args: Vec<String> = args.into_iter().map(Into::into).collect();
 
-    let mut items = Vec::new();
+    let mut occurrences: Vec<(&Flag, usize, Vec<String>)> = Vec::new();
     for (index, arg) in args.iter().enumerate() {
 
         if !arg.starts_with("-") {
@@ -149,42 +207,206 @@ pub fn build_flag_summaries<C, A, T>(command: &Command<C>, args: A) -> Result<Ve
             break;
         }
 
-        let flag = match command.flags().iter().find(|&f| {
-            *arg == format!("{}{}", "--", &f.name())
-            || f.alias().is_some() && *arg == format!("{}{}", "-", f.alias().as_ref().unwrap())
-        }) {
-            Some(f) => f,
-            None => return Err(Error::new(ErrorKind::UnknownFlag(arg.to_string()))),
-        };
+        for (flag, attached) in match_flag_arg(command, arg)?.into_iter() {
+            let value = match (flag.accepts_value(), attached) {
+                (true, Some(value)) => Some(value),
+                (true, None) => match args.get(index + 1) {
+                    Some(value) => match value.starts_with("-") {
+                        true => return Err(Error::new(ErrorKind::MissingFlagValue(arg.to_string()))),
+                        false => Some(value.to_string()),
+                    },
+                    None => return Err(Error::new(ErrorKind::MissingFlagValue(arg.to_string()))),
+                },
+                (false, attached) if flag.is_negatable() => Some(attached.unwrap_or_else(|| "true".to_string())),
+                (false, _) => None,
+            };
 
-        let value = match flag.accepts_value() {
-            true => match args.get(index + 1) {
-                Some(value) => match value.starts_with("-") {
-                    true => return Err(Error::new(ErrorKind::MissingFlagValue(arg.to_string()))),
-                    false => Some(value.to_string()),
+            match occurrences.iter_mut().find(|(f, _, _)| f.name() == flag.name()) {
+                Some((_, count, values)) if flag.is_multiple() || flag.is_count() => {
+                    *count += 1;
+                    values.extend(value);
                 },
-                None => return Err(Error::new(ErrorKind::MissingFlagValue(arg.to_string()))),
+                Some(_) => return Err(Error::new(ErrorKind::DuplicateFlag(arg.to_string()))),
+                None => occurrences.push((flag, 1, value.into_iter().collect())),
+            }
+        }
+    }
+
+    let mut items = Vec::new();
+    for flag in command.flags().iter() {
+        let occurrence = occurrences.iter().find(|(f, _, _)| f.name() == flag.name());
+        let count = occurrence.map_or(0, |o| o.1);
+        let (fallback_count, fallback) = build_flag_fallback(flag, vars, config)?;
+        let invalid = || ErrorKind::InvalidFlagValue(flag.name().to_string());
+        check_choices(invalid, occurrence.map_or(&fallback, |o| &o.2), flag.choices(), command.suggestion_distance())?;
+        if count > 0 || fallback.is_empty() {
+            if let Some(min) = flag.min_occurrences().filter(|min| count < *min) {
+                return Err(Error::new(ErrorKind::TooFewFlagOccurrences(flag.name().to_string(), min, count)));
+            }
+        }
+        if let Some(max) = flag.max_occurrences().filter(|max| count > *max) {
+            return Err(Error::new(ErrorKind::TooManyFlagOccurrences(flag.name().to_string(), max, count)));
+        }
+        if count == 0 {
+            if flag.is_required() && fallback.is_empty() {
+                return Err(Error::new(ErrorKind::MissingRequiredFlag(flag.name().to_string())));
+            }
+            items.push(build_flag_summary(flag, 0, &fallback).with_count(fallback_count));
+        }
+    }
+    for (flag, count, values) in occurrences.iter() {
+        items.push(build_flag_summary(flag, *count, values));
+    }
+    items.sort_by_key(|a| a.name().to_lowercase());
+    Ok(items)
+}
+
+/// Returns the count and values of a flag which was not provided in arguments
+/// where an environment variable takes precedence over the configuration which
+/// takes precedence over the default value. Values of a switch from the
+/// environment or configuration are normalized to `true` or `false` while a
+/// count flag reads its count from them.
+fn build_flag_fallback(flag: &Flag, vars: &HashMap<String, String>, config: &HashMap<String, Vec<String>>) -> Result<(usize, Vec<String>)> {
+    let values = if let Some(value) = flag.env().as_ref().and_then(|name| vars.get(name)) {
+        vec![value.to_string()]
+    } else if let Some(values) = config.get(flag.name()) {
+        values.clone()
+    } else {
+        return Ok((0, flag.default_value().iter().cloned().collect()));
+    };
+    let invalid = || Error::new(ErrorKind::InvalidFlagValue(flag.name().to_string()));
+    if flag.accepts_value() {
+        return Ok((0, values));
+    } else if flag.is_count() {
+        let count = match values.last() {
+            Some(value) => value.parse().map_err(|_| invalid())?,
+            None => 0,
+        };
+        return Ok((count, values));
+    }
+    let values = values.iter().map(|value| match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok("true".to_string()),
+        "0" | "false" | "no" | "off" | "" => Ok("false".to_string()),
+        _ => Err(invalid()),
+    }).collect::<Result<Vec<String>>>()?;
+    Ok((0, values))
+}
+
+/// Returns flags matched by a flag argument with optional attached values.
+/// Besides `--name` and `-alias` this expands clusters of single-character
+/// aliases (`-abc`) where a value-taking flag takes the rest of the cluster as
+/// its value (`-ofile`). A negated flag (`--no-name`) is returned with the
+/// attached value `false`.
+pub fn match_flag_arg<'a, C>(command: &'a Command<C>, arg: &str) -> Result<Vec<(&'a Flag, Option<String>)>> {
+    if let Some(flag) = command.flags().iter().find(|f| {
+        *arg == format!("--{}", f.name())
+        || f.alias().is_some() && *arg == format!("-{}", f.alias().as_ref().unwrap())
+    }) {
+        return Ok(vec![(flag, None)]);
+    } else if let Some(flag) = command.flags().iter().find(|f| f.is_negatable() && *arg == format!("--no-{}", f.name())) {
+        return Ok(vec![(flag, Some("false".to_string()))]);
+    } else if arg.starts_with("--") && arg.len() > 2 && command.accepts_prefixes() {
+        let matches: Vec<&Flag> = command.flags().iter()
+            .filter(|f| f.name().starts_with(&arg[2..]))
+            .collect();
+        match matches.len() {
+            0 => return Err(unknown_flag(command, arg)),
+            1 => return Ok(vec![(matches[0], None)]),
+            _ => {
+                let names = matches.iter().map(|f| format!("--{}", f.name())).collect();
+                return Err(Error::new(ErrorKind::AmbiguousFlag(arg.to_string())).with_suggestions(names));
             },
-            false => None,
+        }
+    } else if arg.starts_with("--") || arg.len() < 2 {
+        return Err(unknown_flag(command, arg));
+    }
+
+    let mut items = Vec::new();
+    for (index, ch) in arg.char_indices().skip(1) {
+        let alias = ch.to_string();
+        let flag = match command.flags().iter().find(|f| f.alias().as_ref() == Some(&alias)) {
+            Some(flag) => flag,
+            None => return Err(unknown_flag(command, &format!("-{}", ch))),
         };
-    
-        items.push(build_flag_summary(flag, true, &value));
-    }
- 
-    for flag in command.flags().into_iter() {
-        let exists = &items.iter().any(|f| f.name() == flag.name());
-        if !exists {
-            let value = match flag.default_value() {
-                Some(v) => Some(v.to_string()),
-                None => None,
-            };
-            items.push(build_flag_summary(flag, false, &value));
+        let rest = &arg[index + ch.len_utf8()..];
+        if flag.accepts_value() && !rest.is_empty() {
+            items.push((flag, Some(rest.to_string())));
+            break;
         }
+        items.push((flag, None));
     }
-    items.sort_by(|a, b| a.name().to_lowercase().cmp(&b.name().to_lowercase()));
     Ok(items)
 }
 
+/// Returns the number of arguments taken by `arg` when it consists of global
+/// flags only (e.g. `2` for `--output file`).
+pub fn global_flag_width<C>(command: &Command<C>, arg: &str) -> Option<usize> {
+    let matches = match_flag_arg(command, arg).ok()?;
+    if !matches.iter().all(|(flag, _)| flag.is_global()) {
+        return None;
+    }
+    match matches.last() {
+        Some((flag, None)) if flag.accepts_value() => Some(2),
+        _ => Some(1),
+    }
+}
+
+/// Returns the unknown flag error with suggestions among the command's flag
+/// names and aliases.
+fn unknown_flag<C>(command: &Command<C>, arg: &str) -> Error {
+    let mut candidates = Vec::new();
+    for flag in command.flags().iter() {
+        candidates.push((flag.name().to_string(), format!("--{}", flag.name())));
+        if flag.is_negatable() {
+            candidates.push((format!("no-{}", flag.name()), format!("--no-{}", flag.name())));
+        }
+        if let Some(alias) = flag.alias() {
+            candidates.push((alias.to_string(), format!("-{}", alias)));
+        }
+    }
+    let suggestions = build_suggestions(arg.trim_start_matches('-'), candidates, command.suggestion_distance());
+    Error::new(ErrorKind::UnknownFlag(arg.to_string())).with_suggestions(suggestions)
+}
+
+/// Returns labels of `candidates` (name and label pairs) whose names are
+/// within `distance` edits of `input` (defaults to 2) ordered by similarity.
+/// A candidate never qualifies when every character of the input had to be
+/// changed.
+pub fn build_suggestions(input: &str, candidates: Vec<(String, String)>, distance: Option<usize>) -> Vec<String> {
+    let limit = distance.unwrap_or(2).min(input.chars().count().saturating_sub(1));
+    let mut items: Vec<(usize, String)> = candidates.into_iter()
+        .map(|(name, label)| (edit_distance(input, &name), label))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    items.sort_by_key(|(distance, _)| *distance);
+    let mut labels: Vec<String> = Vec::new();
+    for (_, label) in items.into_iter() {
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    labels
+}
+
+/// Returns the Levenshtein distance between two strings.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = match ca == *cb {
+                true => previous,
+                false => 1 + previous.min(row[j]).min(row[j + 1]),
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
 /// Returns param summary objects for command. 
 pub fn build_param_summaries<C, A, T>(command: &Command<C>, args: A) -> Result<Vec<ParamSummary>>
     where
@@ -201,21 +423,22 @@ pub fn build_param_summaries<C, A, T>(command: &Command<C>, args: A) -> Result<V
         if arg == "--" {
             break;
         } else if arg.starts_with("-") {
-            if let Some(flag) = command.flags().iter().find(|f| {
-                arg.starts_with("--") && f.name().eq(&arg[2..].to_string())
-                || arg.starts_with("-") && f.alias().eq(&Some(arg[1..].to_string()))
-            }) {
-                if flag.accepts_value() {
-                    args.pop();
+            if stage != 0 || global_flag_width(command, &arg).is_none() {
+                stage = 1;
+            }
+            if let Ok(matches) = match_flag_arg(command, &arg) {
+                if let Some((flag, None)) = matches.last() {
+                    if flag.accepts_value() {
+                        args.pop();
+                    }
                 }
             }
-            stage = 1;
             continue;
         } else if stage == 1 && !arg.starts_with("-") {
             stage = 2;
         } else if stage == 0 {
-            if let Some(subcmd) = command.commands().iter().find(|c| c.name().eq(&arg)) {
-                command = subcmd;
+            if let Ok(Some(index)) = find_subcommand(command, &arg) {
+                command = &command.commands()[index];
                 continue;
             } else {
                 stage = 2;
@@ -226,28 +449,98 @@ pub fn build_param_summaries<C, A, T>(command: &Command<C>, args: A) -> Result<V
         }
     }
 
-    let mut params = command.params().clone();
-    params.reverse();
-
-    let params_count = params.len();
+    let params = command.params();
+    if let Some(param) = params.iter().filter(|p| p.is_variadic()).nth(1) {
+        return Err(Error::new(ErrorKind::MultipleVariadicParams(param.name().to_string())));
+    }
+    let variadic = params.iter().position(|p| p.is_variadic());
+    let fixed_count = params.len() - variadic.map_or(0, |_| 1);
     let input_count = inputs.len();
-    if params_count < input_count {
-        return Err(Error::new(ErrorKind::ToManyParams(params_count, input_count)));
+    if variadic.is_none() && fixed_count < input_count {
+        return Err(Error::new(ErrorKind::ToManyParams(fixed_count, input_count)));
+    }
+
+    // When there are fewer inputs than params the required params are filled
+    // first and the remaining inputs go to the optional params from the end,
+    // while the variadic param takes the surplus.
+    let mut assigned: Vec<Vec<String>> = vec![Vec::new(); params.len()];
+    let mut inputs = inputs.into_iter();
+    let fixed: Vec<usize> = (0..params.len()).filter(|i| Some(*i) != variadic).collect();
+    if input_count <= fixed_count {
+        let needed = |i: &usize| params[*i].is_required() && params[*i].default_value().is_none();
+        let mut filled: Vec<usize> = fixed.iter().copied().filter(needed).take(input_count).collect();
+        let optional: Vec<usize> = fixed.iter().copied().filter(|i| !needed(i)).collect();
+        filled.extend(optional.iter().rev().take(input_count - filled.len()));
+        filled.sort_unstable();
+        for index in filled.iter() {
+            assigned[*index] = inputs.next().into_iter().collect();
+        }
+    } else {
+        let variadic = variadic.unwrap();
+        for (index, values) in assigned.iter_mut().enumerate() {
+            *values = match index == variadic {
+                true => inputs.by_ref().take(input_count - fixed_count).collect(),
+                false => inputs.next().into_iter().collect(),
+            };
+        }
     }
 
     let mut items = Vec::new();
-    for (index, param) in params.iter().enumerate() {
-        let input = match inputs.get(index) {
-            Some(input) => Some(input.to_string()),
-            None => None,
-        };
-        items.push(build_param_summary(param, input.is_some(), &input));
+    for (param, values) in params.iter().zip(assigned.iter()) {
+        let count = values.len();
+        if param.is_variadic() && (count > 0 || param.default_value().is_none()) {
+            if let Some(min) = param.min_values().filter(|min| count < *min) {
+                return Err(Error::new(ErrorKind::TooFewParamValues(param.name().to_string(), min, count)));
+            }
+        }
+        if let Some(max) = param.max_values().filter(|max| param.is_variadic() && count > *max) {
+            return Err(Error::new(ErrorKind::TooManyParamValues(param.name().to_string(), max, count)));
+        }
+        if values.is_empty() && param.is_required() && param.default_value().is_none() {
+            return Err(Error::new(ErrorKind::MissingRequiredParam(param.name().to_string())));
+        }
+        let invalid = || ErrorKind::InvalidParamValue(items.len());
+        check_choices(invalid, values, param.choices(), command.suggestion_distance())?;
+        items.push(build_param_summary(param, values));
     }
-    items.reverse();
-    
+
     Ok(items)
 }
 
+/// Returns an error of the kind built by `invalid` when a value is not among
+/// non-empty `choices` where the closest choice is suggested.
+fn check_choices<F>(invalid: F, values: &[String], choices: &[String], distance: Option<usize>) -> Result<()>
+    where
+    F: Fn() -> ErrorKind,
+{
+    match values.iter().find(|v| !choices.is_empty() && !choices.contains(v)) {
+        Some(value) => {
+            let candidates = choices.iter().map(|c| (c.to_string(), c.to_string())).collect();
+            let suggestions = build_suggestions(value, candidates, distance).into_iter().take(1).collect();
+            Err(Error::new(invalid()).with_suggestions(suggestions).with_choices(choices.to_vec()))
+        },
+        None => Ok(()),
+    }
+}
+
+/// Returns arguments which follow the `--` separator checked against the
+/// command's tail policy.
+pub fn build_tail<C>(command: &Command<C>, args: &[String]) -> Result<Vec<String>> {
+    let tail: Vec<String> = match args.iter().position(|a| a == "--") {
+        Some(index) => args[index + 1..].to_vec(),
+        None => Vec::new(),
+    };
+    match command.tail() {
+        Some(Tail::Required) if tail.is_empty() => {
+            Err(Error::new(ErrorKind::MissingTail(command.name().to_string())))
+        },
+        Some(Tail::Forbidden) if !tail.is_empty() => {
+            Err(Error::new(ErrorKind::UnexpectedTail(command.name().to_string())))
+        },
+        _ => Ok(tail),
+    }
+}
+
 /// Returns resource summary objects for command. 
 pub fn build_resource_summaries<C>(command: &Command<C>) -> Vec<ResourceSummary> {
     command.resources().iter().map(|r| {
@@ -258,6 +551,8 @@ pub fn build_resource_summaries<C>(command: &Command<C>) -> Vec<ResourceSummary>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{args, flag_summaries};
+    use std::collections::HashMap;
     use crate::Context;
 
     #[test]
@@ -266,6 +561,68 @@ mod tests {
             split_equal_args(&vec!["aa", "bb=11", "--cc=22", "-d=33"].iter().map(|s| s.to_string()).collect()),
             vec!["aa", "bb", "11", "--cc", "22", "-d", "33"],
         );
+        assert_eq!(
+            split_equal_args(&["--aa=1", "--", "--bb=2"].iter().map(|s| s.to_string()).collect::<Vec<String>>()),
+            vec!["--aa", "1", "--", "--bb=2"],
+        );
+    }
+
+    #[test]
+    fn builds_suggestions() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        let candidates = || vec![
+            ("status".to_string(), "status".to_string()),
+            ("stash".to_string(), "stash".to_string()),
+            ("log".to_string(), "log".to_string()),
+        ];
+        assert_eq!(build_suggestions("stat", candidates(), None), vec!["status", "stash"]);
+        assert_eq!(build_suggestions("stas", candidates(), None), vec!["stash", "status"]);
+        assert_eq!(build_suggestions("statu", candidates(), Some(1)), vec!["status"]);
+        assert_eq!(build_suggestions("x", candidates(), None), Vec::<String>::new());
+    }
+
+    #[test]
+    fn finds_subcommands() {
+        let command = || Command::<Context>::with_name("a")
+            .with_subcommand(Command::with_name("status").with_alias("st"))
+            .with_subcommand(Command::with_name("stash"))
+            .with_subcommand(Command::with_name("remove").with_alias("rm"));
+        assert_eq!(find_subcommand(&command(), "rm").unwrap(), Some(2));
+        assert_eq!(find_subcommand(&command(), "st").unwrap(), Some(0));
+        assert_eq!(find_subcommand(&command(), "rem").unwrap(), None);
+        assert_eq!(find_subcommand(&command().accept_prefixes(), "rem").unwrap(), Some(2));
+        assert_eq!(find_subcommand(&command().accept_prefixes(), "stat").unwrap(), Some(0));
+        let err = find_subcommand(&command().accept_prefixes(), "sta").unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::AmbiguousCommand("sta".to_string()));
+        assert_eq!(err.suggestions(), &["status", "stash"]);
+    }
+
+    #[test]
+    fn matches_flag_prefixes() {
+        let command = Command::<Context>::with_name("a")
+            .with_flag(Flag::with_name("verbose"))
+            .with_flag(Flag::with_name("version"))
+            .with_flag(Flag::with_name("force"))
+            .accept_prefixes();
+        assert_eq!(match_flag_arg(&command, "--fo").unwrap()[0].0.name(), "force");
+        assert_eq!(match_flag_arg(&command, "--verb").unwrap()[0].0.name(), "verbose");
+        let err = match_flag_arg(&command, "--ver").unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::AmbiguousFlag("--ver".to_string()));
+        assert_eq!(err.suggestions(), &["--verbose", "--version"]);
+        assert_eq!(match_flag_arg(&command, "--x").unwrap_err().kind(), &ErrorKind::UnknownFlag("--x".to_string()));
+    }
+
+    #[test]
+    fn builds_tail() {
+        let command = Command::<Context>::with_name("aaa");
+        assert_eq!(build_tail(&command, &args(&["x", "--", "y", "--", "-z"])).unwrap(), vec!["y", "--", "-z"]);
+        assert_eq!(build_tail(&command, &args(&["x"])).unwrap(), Vec::<String>::new());
+        let command = Command::<Context>::with_name("aaa").with_tail(Tail::Forbidden);
+        assert_eq!(
+            build_tail(&command, &args(&["--", "y"])).unwrap_err().kind(),
+            &ErrorKind::UnexpectedTail("aaa".to_string()),
+        );
     }
 
     #[test]
@@ -306,8 +663,8 @@ mod tests {
             .with_flag(Flag::with_name("ccc").with_alias("c").accept_value())
             .with_flag(Flag::with_name("ddd").with_alias("d"))
             .with_flag(Flag::with_name("eee"));
-        let summaries0 = build_flag_summaries(&command, vec!["cmd", "--aaa", "-c", "cval", "--eee", "--"]).unwrap();
-        let summaries1 = build_flag_summaries(&command, vec!["--aaa", "-c", "cval", "--eee"]).unwrap();
+        let summaries0 = flag_summaries(&command, vec!["cmd", "--aaa", "-c", "cval", "--eee", "--"]).unwrap();
+        let summaries1 = flag_summaries(&command, vec!["--aaa", "-c", "cval", "--eee"]).unwrap();
         let provided0: Vec<FlagSummary> = summaries0.iter().filter(|s| s.provided()).cloned().collect();
         let provided1: Vec<FlagSummary> = summaries1.iter().filter(|s| s.provided()).cloned().collect();
         let names0: Vec<String> = provided0.iter().map(|s| s.name().clone()).collect();
@@ -348,4 +705,239 @@ mod tests {
         assert_eq!(names1, ["ddd"]);
         assert_eq!(names2, ["ddd"]);
     }
+
+    #[test]
+    fn assigns_param_values_in_order() {
+        let command = Command::<Context>::with_name("")
+            .with_param(Param::with_name("aaa"))
+            .with_param(Param::with_name("bbb"))
+            .with_param(Param::with_name("ccc"));
+        let summaries = build_param_summaries(&command, vec!["x", "y"]).unwrap();
+        let values: Vec<Option<String>> = summaries.iter().map(|s| s.value().clone()).collect();
+        assert_eq!(values, [None, Some("x".to_string()), Some("y".to_string())]);
+        let summaries = build_param_summaries(&command, vec!["x", "y", "z"]).unwrap();
+        let values: Vec<Option<String>> = summaries.iter().map(|s| s.value().clone()).collect();
+        assert_eq!(values, [Some("x".to_string()), Some("y".to_string()), Some("z".to_string())]);
+    }
+
+    #[test]
+    fn builds_multiple_flag_summaries() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("aaa").accept_value().multiple().with_min_occurrences(2).with_max_occurrences(3))
+            .with_flag(Flag::with_name("bbb").accept_value());
+        let summaries = flag_summaries(&command, vec!["--aaa", "x", "--bbb", "y", "--aaa", "z"]).unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].values(), &["x", "z"]);
+        assert_eq!(summaries[0].value(), &Some("x".to_string()));
+        assert_eq!(summaries[1].values(), &["y"]);
+        assert_eq!(
+            flag_summaries(&command, vec!["--aaa", "x"]).unwrap_err().kind(),
+            &ErrorKind::TooFewFlagOccurrences("aaa".to_string(), 2, 1),
+        );
+        assert_eq!(
+            flag_summaries(&command, vec!["--aaa", "x", "--aaa", "x", "--aaa", "x", "--aaa", "x"]).unwrap_err().kind(),
+            &ErrorKind::TooManyFlagOccurrences("aaa".to_string(), 3, 4),
+        );
+        assert_eq!(
+            flag_summaries(&command, vec!["--aaa", "x", "--aaa", "x", "--bbb", "y", "--bbb", "z"]).unwrap_err().kind(),
+            &ErrorKind::DuplicateFlag("--bbb".to_string()),
+        );
+    }
+
+    #[test]
+    fn builds_count_flag_summaries() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("verbose").with_alias("v").count())
+            .with_flag(Flag::with_name("aaa"));
+        let summaries = flag_summaries(&command, vec!["-vvv", "--verbose", "--aaa"]).unwrap();
+        assert_eq!(summaries[0].count(), 1);
+        assert_eq!(summaries[1].count(), 4);
+        let summaries = flag_summaries(&command, Vec::<String>::new()).unwrap();
+        assert_eq!(summaries[1].count(), 0);
+        assert!(flag_summaries(&command, vec!["-vvx"]).is_err());
+    }
+
+    #[test]
+    fn builds_clustered_flag_summaries() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("extract").with_alias("x"))
+            .with_flag(Flag::with_name("gzip").with_alias("z"))
+            .with_flag(Flag::with_name("file").with_alias("f").accept_value())
+            .with_flag(Flag::with_name("xyz").with_alias("xz"))
+            .with_param(Param::with_name("aaa"));
+        let summaries = flag_summaries(&command, vec!["-xzf", "a.tgz"]).unwrap();
+        let provided: Vec<&String> = summaries.iter().filter(|s| s.provided()).map(|s| s.name()).collect();
+        assert_eq!(provided, ["extract", "file", "gzip"]);
+        assert_eq!(summaries[1].value(), &Some("a.tgz".to_string()));
+        let summaries = flag_summaries(&command, vec!["-xfa.tgz"]).unwrap();
+        assert_eq!(summaries[1].value(), &Some("a.tgz".to_string()));
+        let summaries = flag_summaries(&command, vec!["-xz"]).unwrap();
+        let provided: Vec<&String> = summaries.iter().filter(|s| s.provided()).map(|s| s.name()).collect();
+        assert_eq!(provided, ["xyz"]);
+        assert_eq!(
+            flag_summaries(&command, vec!["-xqf"]).unwrap_err().kind(),
+            &ErrorKind::UnknownFlag("-q".to_string()),
+        );
+        let summaries = build_param_summaries(&command, vec!["-xzf", "a.tgz", "b"]).unwrap();
+        assert_eq!(summaries[0].value(), &Some("b".to_string()));
+    }
+
+    #[test]
+    fn builds_flag_summaries_from_env() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("aaa").accept_value().with_env("AAA").with_default_value("default"))
+            .with_flag(Flag::with_name("bbb").accept_value().with_env("BBB").required());
+        let mut vars = HashMap::new();
+        vars.insert("AAA".to_string(), "env".to_string());
+        vars.insert("BBB".to_string(), "env".to_string());
+        let summaries = build_flag_summaries(&command, vec!["--bbb", "cli"], &vars, &HashMap::new()).unwrap();
+        assert_eq!(summaries[0].value(), &Some("env".to_string()));
+        assert!(!summaries[0].provided());
+        assert_eq!(summaries[1].value(), &Some("cli".to_string()));
+        vars.remove("AAA");
+        let summaries = build_flag_summaries(&command, Vec::<String>::new(), &vars, &HashMap::new()).unwrap();
+        assert_eq!(summaries[0].value(), &Some("default".to_string()));
+        assert_eq!(summaries[1].value(), &Some("env".to_string()));
+        vars.remove("BBB");
+        assert_eq!(
+            build_flag_summaries(&command, Vec::<String>::new(), &vars, &HashMap::new()).unwrap_err().kind(),
+            &ErrorKind::MissingRequiredFlag("bbb".to_string()),
+        );
+    }
+
+    #[test]
+    fn builds_flag_summaries_from_config() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("aaa").accept_value().with_env("AAA").with_default_value("default"))
+            .with_flag(Flag::with_name("bbb").accept_value().multiple());
+        let mut vars = HashMap::new();
+        let mut config = HashMap::new();
+        config.insert("aaa".to_string(), vec!["config".to_string()]);
+        config.insert("bbb".to_string(), vec!["x".to_string(), "y".to_string()]);
+        let summaries = build_flag_summaries(&command, Vec::<String>::new(), &vars, &config).unwrap();
+        assert_eq!(summaries[0].value(), &Some("config".to_string()));
+        assert_eq!(summaries[1].values(), &["x", "y"]);
+        vars.insert("AAA".to_string(), "env".to_string());
+        let summaries = build_flag_summaries(&command, vec!["--bbb", "z"], &vars, &config).unwrap();
+        assert_eq!(summaries[0].value(), &Some("env".to_string()));
+        assert_eq!(summaries[1].values(), &["z"]);
+    }
+
+    #[test]
+    fn normalizes_switch_fallbacks() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("aaa").with_env("AAA"))
+            .with_flag(Flag::with_name("bbb"));
+        let mut vars = HashMap::new();
+        let mut config = HashMap::new();
+        vars.insert("AAA".to_string(), "1".to_string());
+        config.insert("bbb".to_string(), vec!["no".to_string()]);
+        let summaries = build_flag_summaries(&command, Vec::<String>::new(), &vars, &config).unwrap();
+        assert_eq!(summaries[0].to_bool(), Some(true));
+        assert!(!summaries[0].provided());
+        assert_eq!(summaries[1].to_bool(), Some(false));
+        vars.insert("AAA".to_string(), "x".to_string());
+        assert_eq!(
+            build_flag_summaries(&command, Vec::<String>::new(), &vars, &config).unwrap_err().kind(),
+            &ErrorKind::InvalidFlagValue("aaa".to_string()),
+        );
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("aaa").with_default_value("foo"));
+        let summaries = flag_summaries(&command, Vec::<String>::new()).unwrap();
+        assert_eq!(summaries[0].value(), &Some("foo".to_string()));
+    }
+
+    #[test]
+    fn counts_flag_fallbacks() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("verbose").with_alias("v").count().with_env("VERBOSE"))
+            .with_flag(Flag::with_name("quiet").count());
+        let mut vars = HashMap::new();
+        let mut config = HashMap::new();
+        vars.insert("VERBOSE".to_string(), "3".to_string());
+        config.insert("quiet".to_string(), vec!["2".to_string()]);
+        let summaries = build_flag_summaries(&command, Vec::<String>::new(), &vars, &config).unwrap();
+        assert_eq!(summaries[0].count(), 2);
+        assert!(!summaries[0].provided());
+        assert_eq!(summaries[1].count(), 3);
+        let summaries = build_flag_summaries(&command, vec!["-v"], &vars, &config).unwrap();
+        assert_eq!(summaries[1].count(), 1);
+        vars.insert("VERBOSE".to_string(), "yes".to_string());
+        assert_eq!(
+            build_flag_summaries(&command, Vec::<String>::new(), &vars, &config).unwrap_err().kind(),
+            &ErrorKind::InvalidFlagValue("verbose".to_string()),
+        );
+    }
+
+    #[test]
+    fn builds_variadic_param_summaries() {
+        let command = Command::<Context>::with_name("")
+            .with_param(Param::with_name("src").variadic().with_min_values(1).with_max_values(3))
+            .with_param(Param::with_name("dest"));
+        let summaries = build_param_summaries(&command, vec!["a", "b", "c"]).unwrap();
+        assert_eq!(summaries[0].values(), &["a", "b"]);
+        assert_eq!(summaries[1].values(), &["c"]);
+        assert_eq!(summaries[1].value(), &Some("c".to_string()));
+        assert_eq!(
+            build_param_summaries(&command, vec!["a"]).unwrap_err().kind(),
+            &ErrorKind::TooFewParamValues("src".to_string(), 1, 0),
+        );
+        assert_eq!(
+            build_param_summaries(&command, vec!["a", "b", "c", "d", "e"]).unwrap_err().kind(),
+            &ErrorKind::TooManyParamValues("src".to_string(), 3, 4),
+        );
+        let command = Command::<Context>::with_name("")
+            .with_param(Param::with_name("aaa"))
+            .with_param(Param::with_name("bbb").variadic());
+        let summaries = build_param_summaries(&command, vec!["1", "2", "3"]).unwrap();
+        assert_eq!(summaries[0].values(), &["1"]);
+        assert_eq!(summaries[1].to_values::<usize>(), Some(vec![2, 3]));
+        let summaries = build_param_summaries(&command, vec!["1"]).unwrap();
+        assert_eq!(summaries[0].values(), &["1"]);
+        assert!(!summaries[1].provided());
+    }
+
+    #[test]
+    fn checks_required_flags() {
+        let command = Command::<Context>::with_name("")
+            .with_flag(Flag::with_name("aaa").required())
+            .with_flag(Flag::with_name("bbb").accept_value().with_default_value("x").required());
+        assert!(flag_summaries(&command, vec!["--aaa"]).is_ok());
+        assert_eq!(
+            flag_summaries(&command, vec!["--bbb", "y"]).unwrap_err().kind(),
+            &ErrorKind::MissingRequiredFlag("aaa".to_string()),
+        );
+    }
+
+    #[test]
+    fn rejects_multiple_variadic_params() {
+        let command = Command::<Context>::with_name("")
+            .with_param(Param::with_name("aaa").variadic())
+            .with_param(Param::with_name("bbb").variadic());
+        assert_eq!(
+            build_param_summaries(&command, vec!["a", "b"]).unwrap_err().kind(),
+            &ErrorKind::MultipleVariadicParams("bbb".to_string()),
+        );
+    }
+
+    #[test]
+    fn checks_required_params() {
+        let command = Command::<Context>::with_name("")
+            .with_param(Param::with_name("aaa").required())
+            .with_param(Param::with_name("bbb").with_default_value("x").required());
+        assert!(build_param_summaries(&command, vec!["a", "b"]).is_ok());
+        let summaries = build_param_summaries(&command, vec!["b"]).unwrap();
+        assert_eq!(summaries[0].value(), &Some("b".to_string()));
+        assert!(!summaries[1].provided());
+        assert_eq!(
+            build_param_summaries(&command, Vec::<String>::new()).unwrap_err().kind(),
+            &ErrorKind::MissingRequiredParam("aaa".to_string()),
+        );
+        let command = Command::<Context>::with_name("")
+            .with_param(Param::with_name("src").required())
+            .with_param(Param::with_name("dst"));
+        let summaries = build_param_summaries(&command, vec!["a"]).unwrap();
+        assert_eq!(summaries[0].value(), &Some("a".to_string()));
+        assert!(!summaries[1].provided());
+    }
 }
@@ -0,0 +1,128 @@
+use crate::{Command, Flag, find_subcommand, global_flag_width, match_flag_arg};
+
+/// Returns formatted version text for a command. The long form also includes
+/// the author.
+pub fn render_version<C>(command: &Command<C>, long: bool) -> String {
+    let mut items = vec![match command.version() {
+        Some(version) => format!("{} {}", command.name(), version),
+        None => command.name().to_string(),
+    }];
+    if long {
+        if let Some(author) = command.author() {
+            items.push(author.to_string());
+        }
+    }
+    items.join("\n")
+}
+
+/// Returns the automatic version flag of a command when the command has a
+/// version. The flag is dropped when its name clashes with the command's own
+/// flags while a clashing alias only drops the alias.
+pub(crate) fn active_version_flag<C>(command: &Command<C>) -> Option<Flag> {
+    let flag = match (command.version(), command.version_flag()) {
+        (Some(_), Some(flag)) => flag.clone(),
+        _ => return None,
+    };
+    if command.flags().iter().any(|f| f.name() == flag.name()) {
+        return None;
+    }
+    match command.flags().iter().any(|f| f.alias().is_some() && f.alias() == flag.alias()) {
+        true => Some(flag.without_alias()),
+        false => Some(flag),
+    }
+}
+
+/// Returns `Some(long)` when the version flag of the invoked command is
+/// present among its flags where `long` tells if the flag was provided by its
+/// name rather than its alias. Values of flags and params are not checked.
+pub(crate) fn find_version_request<C>(app: &Command<C>, args: &[String]) -> Option<bool> {
+    let mut command = app;
+    let mut stage = 0; // 0..command, 1..flag or param
+    let mut skip = 0;
+    for arg in args.iter() {
+        if skip > 0 {
+            skip -= 1;
+        } else if arg == "--" {
+            break;
+        } else if arg.starts_with('-') {
+            if let Some(flag) = active_version_flag(command) {
+                if *arg == format!("--{}", flag.name()) {
+                    return Some(true);
+                } else if flag.alias().is_some() && *arg == format!("-{}", flag.alias().as_ref().unwrap()) {
+                    return Some(false);
+                }
+            }
+            if stage != 0 || global_flag_width(command, arg).is_none() {
+                stage = 1;
+            }
+            if let Ok(matches) = match_flag_arg(command, arg) {
+                if let Some((flag, None)) = matches.last() {
+                    if flag.accepts_value() {
+                        skip = 1;
+                    }
+                }
+            }
+        } else if stage == 0 {
+            match find_subcommand(command, arg) {
+                Ok(Some(position)) => command = &command.commands()[position],
+                _ => stage = 1,
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::args;
+    use crate::Context;
+
+    #[test]
+    fn renders_version() {
+        let command = Command::<Context>::with_name("app")
+            .with_version("1.0.0")
+            .with_author("John");
+        assert_eq!(render_version(&command, false), "app 1.0.0");
+        assert_eq!(render_version(&command, true), "app 1.0.0\nJohn");
+    }
+
+    #[test]
+    fn finds_version_request() {
+        let command = Command::<Context>::with_name("app").with_version("1.0.0");
+        assert_eq!(find_version_request(&command, &args(&["--version"])), Some(true));
+        assert_eq!(find_version_request(&command, &args(&["-V"])), Some(false));
+        assert_eq!(find_version_request(&command, &args(&["--", "-V"])), None);
+        let command = Command::<Context>::with_name("app");
+        assert_eq!(find_version_request(&command, &args(&["--version"])), None);
+        let command = Command::<Context>::with_name("app").with_version("1.0.0")
+            .with_version_flag(Flag::with_name("ver"));
+        assert_eq!(find_version_request(&command, &args(&["--version"])), None);
+        assert_eq!(find_version_request(&command, &args(&["--ver"])), Some(true));
+        let command = Command::<Context>::with_name("app").with_version("1.0.0")
+            .without_version_flag();
+        assert_eq!(find_version_request(&command, &args(&["--version"])), None);
+        let command = Command::<Context>::with_name("app").with_version("1.0.0")
+            .with_flag(Flag::with_name("verbose").with_alias("V"));
+        assert_eq!(find_version_request(&command, &args(&["-V"])), None);
+        assert_eq!(find_version_request(&command, &args(&["--version"])), Some(true));
+        let command = Command::<Context>::with_name("app").with_version("1.0.0")
+            .with_flag(Flag::with_name("version"));
+        assert_eq!(find_version_request(&command, &args(&["-V"])), None);
+    }
+
+    #[test]
+    fn finds_version_request_of_invoked_command() {
+        let command = Command::<Context>::with_name("app").with_version("1.0.0")
+            .with_flag(Flag::with_name("tag").accept_value())
+            .with_flag(Flag::with_name("output").accept_value().global())
+            .with_subcommand(Command::with_name("aaa").with_version("2.0.0"))
+            .with_subcommand(Command::with_name("bbb"));
+        assert_eq!(find_version_request(&command, &args(&["--tag", "-V"])), None);
+        assert_eq!(find_version_request(&command, &args(&["--tag", "x", "-V"])), Some(false));
+        assert_eq!(find_version_request(&command, &args(&["--output", "x", "aaa", "-V"])), Some(false));
+        assert_eq!(find_version_request(&command, &args(&["bbb", "--version"])), None);
+        assert_eq!(find_version_request(&command, &args(&["bbb", "--tag", "-V"])), None);
+        assert_eq!(find_version_request(&command, &args(&["xxx", "aaa", "-V"])), Some(false));
+    }
+}